@@ -0,0 +1,46 @@
+use fire_in_the_term::palette;
+use ratatui::style::Color;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Writes the flame's color as a `#RRGGBB` line every frame, for
+/// `--ambient-out`, e.g. for a script driving smart lights in time with the
+/// fire. Write errors can't be shown on the live screen, so the first one is
+/// kept and printed to stderr on drop.
+pub struct AmbientOut {
+    out: BufWriter<File>,
+    error: Option<io::Error>,
+}
+
+impl AmbientOut {
+    pub fn create(path: &Path) -> io::Result<AmbientOut> {
+        Ok(AmbientOut {
+            out: BufWriter::new(File::create(path)?),
+            error: None,
+        })
+    }
+
+    pub fn write(&mut self, color: Color) {
+        if self.error.is_some() {
+            return;
+        }
+        let (r, g, b) = palette::to_rgb(color);
+        // Flushed line by line so a reader gets every frame's color at once
+        if let Err(err) =
+            writeln!(self.out, "#{r:02X}{g:02X}{b:02X}").and_then(|()| self.out.flush())
+        {
+            self.error = Some(err);
+        }
+    }
+}
+
+impl Drop for AmbientOut {
+    fn drop(&mut self) {
+        if let Some(err) = &self.error {
+            eprintln!("Failed to write the ambient color: {err}");
+        }
+    }
+}
@@ -0,0 +1,3191 @@
+use crate::{
+    AppBuilder,
+    config::{Falling, FireConfig, UpdateOrder},
+    effect::Effect,
+    palette::{self, ColorMode, Preset},
+    ramp::{CharRamp, GlyphVariety},
+    sixel,
+};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::{Line, Span, Text},
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    f32::consts::{PI, TAU},
+    mem,
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+/// Heat of a grid cell. The `precise-heat` feature keeps 16 bits per cell for
+/// smoother gradients, which are scaled down to 0-255 for drawing.
+#[cfg(not(feature = "precise-heat"))]
+pub type Heat = u8;
+#[cfg(feature = "precise-heat")]
+pub type Heat = u16;
+
+/// Heat per step of the 0-255 scale that drawing and the config use.
+///
+/// With `precise-heat` the flame settles into many more distinct levels than
+/// the 256 that 8 bits can hold, so its gradient steps less between cells:
+///
+/// ```
+/// use fire_in_the_term::{App, TICK_RATE};
+/// use std::collections::BTreeSet;
+///
+/// let mut app = App::new(80, 40).with_seed(4);
+/// for _ in 0..200 {
+///     app.update_fire(TICK_RATE);
+/// }
+/// let levels: BTreeSet<_> = app.grid().iter().flatten().copied().collect();
+/// if cfg!(feature = "precise-heat") {
+///     assert!(levels.len() > 256);
+/// } else {
+///     assert!(levels.len() <= 256);
+/// }
+/// ```
+pub const HEAT_SCALE: Heat = Heat::MAX / u8::MAX as Heat;
+
+/// Heat the flame was tuned to lose to rounding down in 8 bits, about one and
+/// a half steps per cell each update. Precise heat doesn't lose it, so it is
+/// taken away explicitly to keep the flame the same height.
+const ROUNDING_LOSS: Heat = if cfg!(feature = "precise-heat") {
+    HEAT_SCALE * 3 / 2
+} else {
+    0
+};
+
+/// A hook that edits the heat grid, indexed as `grid[y][x]` with row 0 at
+/// the top, after each simulation step.
+pub type PostProcess = Box<dyn FnMut(&mut Vec<Vec<Heat>>)>;
+
+/// A spontaneous flare-up rising from one spot of the base.
+struct Burst {
+    column: usize,
+    // Frames left until it has died down
+    remaining: f32,
+}
+
+/// A flake of snow or a fleck of ash falling over the flame, at fractional
+/// grid coordinates.
+struct Speck {
+    x: f32,
+    y: f32,
+    // Rows it falls each frame
+    speed: f32,
+}
+
+/// Random numbers for one cell on one step, worked out from the seed, the
+/// cell's column, its row counted from the bottom and the step rather than
+/// drawn in turn from one stream. A seed then gives a cell the same numbers
+/// whatever the grid's size, for `cell_seeding`. Each `stream` gives a
+/// separate set, for the parts of a step that draw their own.
+struct CellRng(u64);
+
+/// Streams of [`CellRng`] for the rise and for lighting the base.
+const RISE_STREAM: u64 = 0;
+const IGNITION_STREAM: u64 = 1;
+
+impl CellRng {
+    fn new(seed: u64, stream: u64, x: usize, y: usize, step: u64) -> CellRng {
+        let state = [stream, x as u64, y as u64, step]
+            .into_iter()
+            .fold(splitmix64(seed), |state, value| splitmix64(state ^ value));
+        CellRng(state)
+    }
+}
+
+impl RngCore for CellRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = splitmix64(self.0);
+        self.0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// Scrambles 64 bits so that nearby inputs come out unrelated, the mixing
+/// step of the SplitMix64 generator.
+fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A fire simulation together with how it is drawn.
+pub struct App {
+    fire_grid: Vec<Vec<Heat>>,
+    // Frames each cell has held its heat since it was last ignited
+    age_grid: Vec<Vec<u8>>,
+    // Glyph variant of each cell when only changed cells are redrawn. It is
+    // only re-picked when the cell's band changes, so the rest of the frame
+    // stays identical and the terminal skips it.
+    glyph_variants: Vec<Vec<u8>>,
+    // The grid each step is worked out into, swapped with `fire_grid` when
+    // done, which base cells the step ignited and the shares of heat taken
+    // from the sides. Kept between steps so a step allocates nothing once
+    // they fit the grid.
+    spare_grid: Vec<Vec<Heat>>,
+    ignited: Vec<bool>,
+    diffusion_weights: Vec<f32>,
+    // The random numbers of the row being worked out, and the heat each of
+    // its cells takes from the left and right
+    rolls: RowRolls,
+    side_sums: [Vec<f32>; 2],
+    // Smooth noise from 0 to 1 that scrolls up with the flame, cooling some
+    // patches more than others. Built on the first update after a resize.
+    cooling_map: Vec<Vec<f32>>,
+    // Coarse field of sideways nudges from -127 to 127, one per block of
+    // `TURBULENCE_CELL` cells, that wanders slowly to make the flame waver.
+    // Built on the first update after a resize.
+    turbulence: Vec<Vec<i8>>,
+    // What is drawn when the blur trail is on: the grid, or the fading last
+    // drawn heat where that is hotter. Never fed back into the simulation.
+    trail_grid: Vec<Vec<Heat>>,
+    // What is drawn when bloom is on: the heat above, brightened by the glow
+    // of nearby hot cells. Empty while bloom is off, and never fed back into
+    // the simulation either.
+    bloom_grid: Vec<Vec<Heat>>,
+    // Ticks each cell keeps showing ash for, set while it burns hot. Built on
+    // the first update with ash on.
+    ash_grid: Vec<Vec<f32>>,
+    // Snow or ash falling over the flame
+    specks: Vec<Speck>,
+    // Fires burning behind this one with `layers`, nearest first
+    behind: Vec<App>,
+    // Dimensions of the simulated grid
+    width: usize,
+    height: usize,
+    // Dimensions of the rendered frame, which differ from the grid when the
+    // simulation runs at a fixed size
+    display_width: usize,
+    display_height: usize,
+    char_map: Vec<Vec<char>>,
+    // Ramp to go back to when block glyphs are toggled off
+    text_ramp: CharRamp,
+    // Ramp to go back to when the safe glyphs are toggled off
+    full_ramp: CharRamp,
+    show_heat: bool,
+    // Draw the heat in shades of gray in place of the palette
+    gray_view: bool,
+    // Draw the wind and turbulence over the flame, for `--debug`
+    wind_view: bool,
+    // Strength of the current flare-up, from 0 up to `FLARE_CAP`. Each unit
+    // lasts `FLARE_FRAMES` frames before it has eased off.
+    flare: f32,
+    // Steady flare-up kept up for as long as it is set, e.g. from the load of
+    // the machine with `--react-load`
+    load_flare: f32,
+    // Ticks in a row the fire has been burning near white all over, and how
+    // much harder it is cooled to recover with `auto_dampen`
+    saturated_ticks: f32,
+    damping: f32,
+    // Steps of time not yet simulated, carried over from the last frame
+    pending_steps: f32,
+    // Frames left before the base is lit again after `relight`
+    relight_frames: f32,
+    // Cleared by `set_lit` to keep the base out until it is lit again
+    lit: bool,
+    // How brightly the bed of embers left by `smolder` glows, from 1 down to 0
+    embers: Option<f32>,
+    // Spontaneous flare-ups running at spots of the base, and the tick the
+    // next one starts at once scheduled
+    bursts: Vec<Burst>,
+    next_burst: Option<f64>,
+    // Drives all the randomness of the simulation, so a seed reproduces a run.
+    // Glyph picking has its own randomness since it doesn't affect the heat,
+    // seeded from entropy unless given a seed of its own. Glyphs are picked
+    // while drawing, which only borrows the app.
+    rng: ChaCha12Rng,
+    seed: u64,
+    glyph_rng: RefCell<ChaCha12Rng>,
+    // Drawn from `glyph_rng` once a frame and mixed with each cell's position
+    // for `GlyphVariety::Hashed`
+    glyph_salt: Cell<u64>,
+    // The most recent grids, oldest first, kept for replaying. Only grids are
+    // stored so the memory stays bounded; they are re-rendered on replay.
+    frame_history: VecDeque<Vec<Vec<Heat>>>,
+    history_capacity: usize,
+    // Most bytes of grids the history may hold, on top of `history_capacity`
+    history_memory: Option<usize>,
+    // Position in `frame_history` while replaying
+    replay_index: Option<usize>,
+    // Wind currently blowing, in cells per row, easing towards the configured
+    // wind plus the automatic sway
+    wind: f32,
+    // How fast gusts are still pushing the wind along, in cells per row per
+    // frame
+    wind_velocity: f32,
+    // Position in the automatic wind's swing, in radians
+    wind_phase: f32,
+    // Where the base burns hottest, as a fraction of the width, and how far
+    // it has got through its wander, in radians
+    base_center: f32,
+    wander_phase: f32,
+    // Palette being faded out after a palette change, and how far the fade to
+    // the configured palette has got, from 0 to 1
+    old_palette: Option<Vec<Color>>,
+    transition_progress: f32,
+    // Whether the flame is drawn upside down, and how far the turn to that
+    // way up has got, from 0 to 1
+    flipped: bool,
+    flip_progress: f32,
+    // Custom effect run on the grid after every simulation step
+    post_process: Option<PostProcess>,
+    // Short message about the last change to a setting, and when it was shown
+    toast: Option<(String, Instant)>,
+    // When the app was created, which the pulse is timed from
+    started: Instant,
+    // Simulated time so far, in ticks of `TICK_RATE`
+    ticks: f64,
+    // Simulation steps taken so far, which `cell_seeding` tells apart by
+    steps: u64,
+    // Whether the grid is short enough to use the compact parameters
+    compact: bool,
+    // The grid is simulated at 1 / `resolution_divisor` of the flame's size
+    // in each direction when no fixed `sim_size` is configured
+    resolution_divisor: usize,
+    // Part of the flame, in cells, that is magnified `zoom` times to fill the
+    // frame. A zoom of 1 shows the whole flame.
+    viewport: Rect,
+    zoom: u16,
+    // Colors the terminal can show, which drawn colors are reduced to
+    color_mode: ColorMode,
+    // Where frames narrower than the area they are drawn into sit in it
+    alignment: Alignment,
+    // How much the fire covers what is already drawn where it is drawn
+    opacity: f32,
+    // How much of each frame column's heat is drawn, tapering towards the
+    // sides with `edge_falloff`. Empty for hard edges.
+    edge_weights: Vec<f32>,
+    // Grid cells that can hold heat under `burn_mask`. Empty without one.
+    burnable: Vec<Vec<bool>>,
+    // Brightness each frame cell is drawn at, by row and column, darkening
+    // towards the corners with `vignette`. Empty without one.
+    vignette: Vec<Vec<f32>>,
+    // Palettes drawn in place of the config's over some rows, and the flame
+    // height their ranges were given for
+    row_palettes: Vec<(Range<usize>, Vec<Color>)>,
+    row_palettes_height: usize,
+    pub config: FireConfig,
+}
+
+/// Frame interval the simulation is tuned for. Every per-frame rate below
+/// applies to a step of this length and is scaled for shorter or longer ones.
+pub const TICK_RATE: Duration = Duration::from_millis(60); // ~16.6 FPS
+
+/// Longest step simulated at once, in ticks, so a stall doesn't make the
+/// flame jump ahead.
+const MAX_STEP_TICKS: f32 = 4.0;
+/// Shortest part of a step simulated on its own, as a fraction of a step.
+/// Less than this is carried over to the next frame instead, since frames are
+/// timed a little over a tick apart and would otherwise take a whole extra
+/// step for a sliver of time.
+const MIN_PARTIAL_STEP: f32 = 0.1;
+
+const FLARE_CAP: f32 = 3.0;
+const FLARE_FRAMES: f32 = 20.0;
+/// Each fire layer behind the flame is drawn this much dimmer and less vivid
+/// than the one in front of it, and moves at this fraction of its speed.
+const LAYER_BRIGHTNESS: f32 = 0.6;
+const LAYER_CONTRAST: f32 = 0.8;
+const LAYER_SPEED: f32 = 0.7;
+/// Flare-up kept up by `App::set_load` at full load.
+const LOAD_FLARE: f32 = 1.5;
+/// Frames the base stays unlit while relighting, long enough for the flame
+/// to burn down.
+const RELIGHT_FRAMES: f32 = 40.0;
+/// Frames taken to turn the flame upside down or back.
+const FLIP_FRAMES: f32 = 12.0;
+
+/// Frames a spontaneous burst lasts, the cells it spreads to on either side
+/// of its column, and how much stronger it makes the rise at its center when
+/// it starts.
+const BURST_FRAMES: f32 = 8.0;
+const BURST_RADIUS: usize = 2;
+const BURST_RISE: f32 = 0.5;
+
+/// A cell counts as freshly ignited when its heat jumps by at least this much
+/// in one frame.
+const IGNITION_SPIKE: Heat = 64 * HEAT_SCALE;
+/// Cells younger than this many frames get the age tint.
+const FRESH_AGE: u8 = 4;
+/// Ash is left where a cell burns at least this hot, and shows for this many
+/// frames once the heat drops below `ASH_MAX_HEAT`.
+const ASH_SOURCE_HEAT: u8 = 128;
+const ASH_FRAMES: f32 = 6.0;
+const ASH_MAX_HEAT: u8 = 32;
+/// Glyph the ash is drawn with.
+const ASH_GLYPH: char = '.';
+/// Heat above which cells glow with `bloom`, and the weights the glow is
+/// spread over neighboring cells with, in each direction.
+const BLOOM_THRESHOLD: u8 = 160;
+const BLOOM_KERNEL: [f32; 7] = [
+    1.0 / 64.0,
+    6.0 / 64.0,
+    15.0 / 64.0,
+    20.0 / 64.0,
+    15.0 / 64.0,
+    6.0 / 64.0,
+    1.0 / 64.0,
+];
+/// Heat of freshly smoldering embers, how fast their glow shimmers, in
+/// radians a frame, and the chance of each one throwing up a spark every
+/// frame.
+const EMBER_HEAT: f32 = 110.0;
+const EMBER_PULSE: f32 = 0.12;
+const EMBER_SPARK_CHANCE: f64 = 0.004;
+/// Mean display heat above which the fire counts as burning near white all
+/// over, and the ticks it has to stay there in a row to count as blown out.
+const SATURATION_HEAT: f32 = 200.0;
+const SATURATION_TICKS: f32 = 50.0;
+/// How much harder `auto_dampen` cools a blown out fire every tick, and the
+/// most it cools it, as a share of the usual decay.
+const DAMPING_RATE: f32 = 0.05;
+const MAX_DAMPING: f32 = 8.0;
+/// How far short of a whole tick the simulated time can fall and still count
+/// as having reached it.
+const TICK_SLACK: f64 = 1e-4;
+/// Most heat a cell flickers by with `coherent_flicker`, a little over the
+/// most of the random flicker since the smooth noise is rarely at its peak.
+const FLICKER_DEPTH: f32 = 20.0;
+/// Chance of a speck starting to fall over each column every frame, and the
+/// range of rows it falls a frame.
+const SPECK_CHANCE: f64 = 0.0015;
+const SPECK_SPEEDS: std::ops::RangeInclusive<f32> = 0.15..=0.35;
+/// Specks melt or burn up in cells at least this hot. Snow takes
+/// `SNOW_COOLING` of the heat with it.
+const SPECK_MELT_HEAT: u8 = 96;
+const SNOW_COOLING: Heat = 24 * HEAT_SCALE;
+/// Most times the view can be magnified.
+const MAX_ZOOM: u16 = 8;
+/// Rows over which a gas flame's blue base fades into the palette colors.
+const GAS_FADE_ROWS: f32 = 3.0;
+/// Coolest heat a gas flame draws with any blue.
+const GAS_MIN_HEAT: f32 = 96.0;
+
+/// Strongest wind the arrow keys can set, in cells per row.
+const WIND_LIMIT: f32 = 2.0;
+/// Fraction of the gap to the target wind closed each frame.
+const WIND_SMOOTHING: f32 = 0.05;
+/// Fraction of a gust's momentum lost each frame, and the most momentum
+/// stacked gusts can build up.
+const GUST_DAMPING: f32 = 0.15;
+const GUST_LIMIT: f32 = 0.6;
+
+/// Chance each frame that a cell along the bottom edge of a burn mask region
+/// catches.
+const MASK_IGNITION_CHANCE: f64 = 0.6;
+
+/// Fraction of the ignition that fades out at the low point of each pulse.
+const PULSE_DEPTH: f64 = 0.4;
+/// Farthest a wandering base moves from the middle, as a fraction of the
+/// width.
+const WANDER_REACH: f32 = 0.3;
+
+/// Cells covered by each point of the turbulence field in both directions.
+const TURBULENCE_CELL: usize = 6;
+/// Frames between changes to the turbulence field.
+const TURBULENCE_STIR_TICKS: u64 = 4;
+
+/// How long a toast stays up.
+const TOAST_DURATION: Duration = Duration::from_secs(1);
+
+/// Color the reflection is tinted towards.
+const REFLECTION_TINT: Color = Color::Rgb(0, 40, 120);
+
+impl App {
+    /// Creates an app seeded from entropy. The chosen seed is available from
+    /// [`App::seed`] so the run can be reproduced with [`App::with_seed`].
+    ///
+    /// Any size works, down to a frame with no cells at all, such as the
+    /// inside of a bordered pane on a terminal one or two rows tall:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, FireWidget, TICK_RATE};
+    /// use ratatui::{Terminal, backend::TestBackend};
+    ///
+    /// for (width, height) in [(0, 0), (1, 1), (80, 1), (0, 5), (5, 0), (80, 2), (1, 2)] {
+    ///     let mut app = App::new(width, height).with_seed(1);
+    ///     for _ in 0..10 {
+    ///         app.update_fire(TICK_RATE);
+    ///     }
+    ///     app.render_fire();
+    ///     app.to_ansi_string();
+    ///     let backend = TestBackend::new(width as u16, height as u16);
+    ///     Terminal::new(backend)
+    ///         .unwrap()
+    ///         .draw(|frame| frame.render_stateful_widget(FireWidget, frame.area(), &mut app))
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn new(width: usize, height: usize) -> App {
+        let config = FireConfig::default();
+        let seed = rand::random();
+        let mut app = App {
+            fire_grid: vec![vec![0; width]; height],
+            age_grid: vec![vec![0; width]; height],
+            glyph_variants: vec![vec![0; width]; height],
+            spare_grid: Vec::new(),
+            ignited: Vec::new(),
+            diffusion_weights: Vec::new(),
+            rolls: RowRolls::default(),
+            side_sums: [Vec::new(), Vec::new()],
+            cooling_map: Vec::new(),
+            turbulence: Vec::new(),
+            trail_grid: vec![vec![0; width]; height],
+            bloom_grid: Vec::new(),
+            ash_grid: Vec::new(),
+            specks: Vec::new(),
+            behind: Vec::new(),
+            width,
+            height,
+            display_width: width,
+            display_height: height,
+            char_map: char_map(&config),
+            text_ramp: CharRamp::default(),
+            full_ramp: CharRamp::default(),
+            show_heat: false,
+            gray_view: false,
+            wind_view: false,
+            flare: 0.0,
+            load_flare: 0.0,
+            saturated_ticks: 0.0,
+            damping: 0.0,
+            pending_steps: 0.0,
+            relight_frames: 0.0,
+            lit: true,
+            embers: None,
+            bursts: Vec::new(),
+            next_burst: None,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            glyph_rng: RefCell::new(ChaCha12Rng::from_os_rng()),
+            glyph_salt: Cell::new(0),
+            seed,
+            frame_history: VecDeque::new(),
+            history_capacity: 0,
+            history_memory: None,
+            replay_index: None,
+            wind: 0.0,
+            wind_velocity: 0.0,
+            wind_phase: 0.0,
+            base_center: 0.5,
+            wander_phase: 0.0,
+            old_palette: None,
+            transition_progress: 1.0,
+            flipped: false,
+            flip_progress: 1.0,
+            post_process: None,
+            toast: None,
+            started: Instant::now(),
+            ticks: 0.0,
+            steps: 0,
+            compact: false,
+            resolution_divisor: 1,
+            viewport: Rect::default(),
+            zoom: 1,
+            color_mode: ColorMode::default(),
+            alignment: Alignment::Center,
+            opacity: 1.0,
+            edge_weights: Vec::new(),
+            burnable: Vec::new(),
+            vignette: Vec::new(),
+            row_palettes: Vec::new(),
+            row_palettes_height: 0,
+            config,
+        };
+        app.resize(width, height);
+        app
+    }
+
+    /// Starts setting up an app; see [`AppBuilder`].
+    pub fn builder() -> AppBuilder {
+        AppBuilder::new()
+    }
+
+    pub fn with_config(mut self, config: FireConfig) -> App {
+        let (width, height) = self.display_size();
+        self.char_map = char_map(&config);
+        self.config = config;
+        self.resize(width, height);
+        self
+    }
+
+    /// Switches to `config` while the fire keeps burning, cross-fading to its
+    /// palette like [`App::set_palette`].
+    pub fn set_config(&mut self, mut config: FireConfig) {
+        let (width, height) = self.display_size();
+        let palette = std::mem::replace(&mut config.palette, self.config.palette.clone());
+        self.config = config;
+        self.damping = 0.0;
+        self.char_map = char_map(&self.config);
+        if palette != self.config.palette {
+            self.set_palette(palette);
+        }
+        self.resize(width, height);
+    }
+
+    /// Reseeds the simulation so it plays out the same way every time. The
+    /// glyphs are still picked at random unless [`App::with_glyph_seed`] fixes
+    /// them too.
+    ///
+    /// Seeded runs use ChaCha12 rather than `rand`'s `StdRng`, whose
+    /// algorithm may change between releases, so a seed keeps giving the
+    /// same flame.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE};
+    ///
+    /// let mut app = App::new(40, 20).with_seed(7);
+    /// for _ in 0..50 {
+    ///     app.update_fire(TICK_RATE);
+    /// }
+    /// let checksum: u64 = app.grid().iter().flatten().map(|&heat| heat as u64).sum();
+    /// # #[cfg(not(feature = "precise-heat"))]
+    /// assert_eq!(checksum, 135402);
+    /// ```
+    pub fn with_seed(mut self, seed: u64) -> App {
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+        self.seed = seed;
+        for (depth, layer) in (1..).zip(&mut self.behind) {
+            layer.rng = ChaCha12Rng::seed_from_u64(seed.wrapping_add(depth));
+            layer.seed = seed.wrapping_add(depth);
+        }
+        self
+    }
+
+    /// Reseeds the picking of glyphs, which is separate from the simulation,
+    /// so a seeded run also draws the same characters every time.
+    pub fn with_glyph_seed(self, seed: u64) -> App {
+        self.glyph_rng.replace(ChaCha12Rng::seed_from_u64(seed));
+        for (depth, layer) in (1..).zip(&self.behind) {
+            (layer.glyph_rng).replace(ChaCha12Rng::seed_from_u64(seed.wrapping_add(depth)));
+        }
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Size of the simulated grid as `(width, height)`.
+    pub fn grid_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// The simulated heat grid, indexed as `grid[y][x]` with row 0 at the top.
+    pub fn grid(&self) -> &[Vec<Heat>] {
+        &self.fire_grid
+    }
+
+    /// Size of the rendered frame as `(width, height)`.
+    pub fn display_size(&self) -> (usize, usize) {
+        if self.config.rotate {
+            (self.display_height, self.display_width)
+        } else {
+            (self.display_width, self.display_height)
+        }
+    }
+
+    /// Runs `post_process` on the heat grid after every simulation step and
+    /// before it is drawn, for custom effects such as carving out text. The
+    /// edited grid is also what the next step builds on.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE};
+    ///
+    /// // Dim the left half of the flame
+    /// let mut app = App::new(80, 24).with_post_process(Box::new(|grid| {
+    ///     for row in grid.iter_mut() {
+    ///         let half = row.len() / 2;
+    ///         for heat in &mut row[..half] {
+    ///             *heat /= 2;
+    ///         }
+    ///     }
+    /// }));
+    /// app.update_fire(TICK_RATE);
+    /// ```
+    pub fn with_post_process(mut self, post_process: PostProcess) -> App {
+        self.post_process = Some(post_process);
+        self
+    }
+
+    /// Draws only colors the terminal can show in `color_mode`. Defaults to
+    /// truecolor.
+    pub fn with_color_mode(mut self, color_mode: ColorMode) -> App {
+        self.color_mode = color_mode;
+        self
+    }
+
+    /// Lines frames up with the left or right side of the area they are drawn
+    /// into when they are narrower than it, instead of centering them.
+    pub fn with_alignment(mut self, alignment: Alignment) -> App {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Moves the frame on to the next alignment, from left to center to right.
+    pub fn cycle_alignment(&mut self) {
+        self.alignment = match self.alignment {
+            Alignment::Left => Alignment::Center,
+            Alignment::Center => Alignment::Right,
+            Alignment::Right => Alignment::Left,
+        };
+    }
+
+    pub fn alignment(&self) -> Alignment {
+        self.alignment
+    }
+
+    /// Lets whatever is already drawn show through the fire when it is drawn
+    /// as a widget, from 0 for an invisible fire to 1, the default, for one
+    /// that covers it. See [`crate::FireWidget`].
+    pub fn with_opacity(mut self, opacity: f32) -> App {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// The color mode the fire's colors are reduced to.
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    /// Draws the flame rows in each range with its own palette instead of the
+    /// config's, e.g. for a warm base fading into a dusky top. Rows count from
+    /// the top of the flame at its current height, and the ranges stretch with
+    /// the flame when it is resized. Where ranges overlap the first one wins.
+    /// Each palette needs at least two colors, and sixel output keeps the
+    /// config's palette throughout.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, palette};
+    /// use ratatui::style::Color;
+    ///
+    /// // Tint the top third of a 30 row flame a dusky purple
+    /// let dusk = vec![Color::Black, Color::Rgb(80, 20, 90), Color::Rgb(200, 120, 220)];
+    /// let mut app = App::new(80, 30).with_row_palettes(vec![
+    ///     (0..10, dusk),
+    ///     (10..30, palette::FIRE.to_vec()),
+    /// ]);
+    /// app.update_fire(TICK_RATE);
+    /// ```
+    pub fn with_row_palettes(mut self, row_palettes: Vec<(Range<usize>, Vec<Color>)>) -> App {
+        self.row_palettes = row_palettes;
+        self.row_palettes_height = self.flame_height();
+        self
+    }
+
+    /// Keeps the last `capacity` frames for replaying.
+    pub fn with_history(mut self, capacity: usize) -> App {
+        self.history_capacity = capacity;
+        self
+    }
+
+    /// Caps the frames kept for replaying to `bytes` of grid, counting
+    /// `width * height` heats per frame. On large grids this keeps fewer
+    /// frames than [`App::with_history`] asks for, dropping the oldest first.
+    pub fn with_history_memory(mut self, bytes: usize) -> App {
+        self.history_memory = Some(bytes);
+        self
+    }
+
+    pub fn set_char_ramp(&mut self, char_ramp: CharRamp) {
+        self.config.char_ramp = char_ramp;
+        self.char_map = char_map(&self.config);
+    }
+
+    /// Fits the fire to a frame of `new_width` by `new_height` cells, keeping
+    /// the flame that is burning, like [`App::set_dimensions`] with
+    /// `preserve`.
+    ///
+    /// Any size will do, down to an empty frame, even in the middle of a
+    /// burning fire:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let configs = [
+    ///     FireConfig::default(),
+    ///     FireConfig { reflect_rows: 3, wide: true, ..FireConfig::default() },
+    ///     FireConfig { rotate: true, turbulence: 0.8, layers: 2, ..FireConfig::default() },
+    /// ];
+    /// let sizes = [(40, 20), (0, 0), (1, 1), (0, 10), (10, 0), (1, 30), (80, 1), (40, 20)];
+    /// for config in configs {
+    ///     let mut app = App::new(40, 20).with_config(config).with_seed(3);
+    ///     for (width, height) in sizes {
+    ///         app.resize(width, height);
+    ///         for _ in 0..5 {
+    ///             app.update_fire(TICK_RATE);
+    ///         }
+    ///         app.render_fire();
+    ///         app.to_ansi_string();
+    ///     }
+    /// }
+    /// ```
+    pub fn resize(&mut self, new_width: usize, new_height: usize) {
+        self.set_dimensions(new_width, new_height, true);
+    }
+
+    /// Fits the fire to a frame of `new_width` by `new_height` cells. With
+    /// `preserve`, the heat already burning is kept, resting on the bottom and
+    /// centered across, and cut off or padded with cold cells where the sizes
+    /// differ. Otherwise the fire starts over from a cold grid. Nothing
+    /// changes when the grid keeps its size.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE};
+    ///
+    /// let mut app = App::new(40, 20).with_seed(1);
+    /// for _ in 0..50 {
+    ///     app.update_fire(TICK_RATE);
+    /// }
+    /// let base = app.grid()[19][..].to_vec();
+    /// app.set_dimensions(60, 30, true);
+    /// // Still at the bottom, 10 columns in from each side
+    /// assert_eq!(app.grid()[29][10..50], base[..]);
+    /// assert!(app.grid()[29][..10].iter().all(|&heat| heat == 0));
+    ///
+    /// app.set_dimensions(40, 20, false);
+    /// assert!(app.grid().iter().flatten().all(|&heat| heat == 0));
+    /// ```
+    pub fn set_dimensions(&mut self, new_width: usize, new_height: usize, preserve: bool) {
+        // A rotated flame is simulated and drawn upright before being turned,
+        // across the frame's height and along its width
+        let (new_width, new_height) = if self.config.rotate {
+            (new_height, new_width)
+        } else {
+            (new_width, new_height)
+        };
+        self.display_width = new_width;
+        self.display_height = new_height;
+        let divisor = self.resolution_divisor();
+        let (columns, rows) = self.subcells();
+        let (new_width, new_height) = self.config.sim_size.unwrap_or((
+            self.flame_width() * columns / divisor,
+            self.flame_height() * rows / divisor,
+        ));
+        // The step needs a row to light and a column to light it in
+        let (new_width, new_height) = (new_width.max(1), new_height.max(1));
+        if self.width != new_width || self.height != new_height {
+            let old_fire = std::mem::take(&mut self.fire_grid);
+            let old_age = std::mem::take(&mut self.age_grid);
+            self.width = new_width;
+            self.height = new_height;
+            self.fire_grid = vec![vec![0; self.width]; self.height];
+            self.age_grid = vec![vec![0; self.width]; self.height];
+            if preserve {
+                anchor_bottom(&old_fire, &mut self.fire_grid);
+                anchor_bottom(&old_age, &mut self.age_grid);
+            }
+            self.glyph_variants = vec![vec![0; self.width]; self.height];
+            self.cooling_map.clear();
+            self.turbulence.clear();
+            self.trail_grid.clone_from(&self.fire_grid);
+            self.bloom_grid.clear();
+            self.ash_grid.clear();
+            self.specks.clear();
+            self.bursts.clear();
+            // Frames of the old size can't be shown anymore
+            self.frame_history.clear();
+            self.replay_index = None;
+        }
+        self.compact = self.height < self.config.compact_threshold;
+        self.sync_layers();
+        self.edge_weights = edge_weights(self.config.edge_falloff, self.flame_width());
+        self.burnable = burnable(&self.config.burn_mask, self.width, self.height);
+        self.vignette = vignette(
+            self.config.vignette,
+            self.flame_width(),
+            self.display_height,
+        );
+        self.set_zoom(self.zoom);
+    }
+
+    /// Replaces the heat of the fire with `grid`, e.g. a frame saved
+    /// earlier, to burn on from there. A grid of another size rests on the
+    /// bottom and is centered across, cut off or padded with cold cells like
+    /// a resize with [`App::set_dimensions`]. Every cell starts out fresh,
+    /// with no age.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE};
+    ///
+    /// let mut burning = App::new(40, 20).with_seed(5);
+    /// for _ in 0..50 {
+    ///     burning.update_fire(TICK_RATE);
+    /// }
+    /// let saved = burning.grid().to_vec();
+    ///
+    /// let mut restored = App::new(40, 20);
+    /// restored.set_grid(&saved);
+    /// assert_eq!(restored.grid(), &saved[..]);
+    ///
+    /// // A wider frame keeps the saved one in the middle of its bottom rows
+    /// let mut wide = App::new(60, 30);
+    /// wide.set_grid(&saved);
+    /// assert_eq!(wide.grid()[29][10..50], saved[19][..]);
+    /// ```
+    pub fn set_grid(&mut self, grid: &[Vec<Heat>]) {
+        for row in &mut self.fire_grid {
+            row.fill(0);
+        }
+        anchor_bottom(grid, &mut self.fire_grid);
+        for row in &mut self.age_grid {
+            row.fill(0);
+        }
+        self.trail_grid.clone_from(&self.fire_grid);
+        self.glow();
+    }
+
+    /// Simulates the grid at 1 / `divisor` of the flame's size in each
+    /// direction and scales it up for drawing, trading detail for speed. The
+    /// flame carries over to the new grid. Has no effect with a fixed
+    /// `sim_size`.
+    pub fn set_resolution_divisor(&mut self, divisor: usize) {
+        let divisor = divisor.max(1);
+        if divisor == self.resolution_divisor {
+            return;
+        }
+        self.resolution_divisor = divisor;
+        let (old_grid, old_width, old_height) = (self.fire_grid.clone(), self.width, self.height);
+        let (width, height) = self.display_size();
+        // The flame is scaled over to the new grid below
+        self.set_dimensions(width, height, false);
+        if (self.width, self.height) != (old_width, old_height) {
+            for (y, row) in self.fire_grid.iter_mut().enumerate() {
+                let old_row = &old_grid[y * old_height / self.height];
+                for (x, heat) in row.iter_mut().enumerate() {
+                    *heat = old_row[x * old_width / self.width];
+                }
+            }
+        }
+    }
+
+    /// The divisor the grid is simulated at, which is at least 2 for flames
+    /// wider than `ultrawide_columns`.
+    pub fn resolution_divisor(&self) -> usize {
+        let ultrawide_columns = self.config.ultrawide_columns;
+        if ultrawide_columns > 0 && self.flame_width() > ultrawide_columns {
+            self.resolution_divisor.max(2)
+        } else {
+            self.resolution_divisor
+        }
+    }
+
+    /// Magnifies the middle of the view twice as much, up to `MAX_ZOOM` times.
+    pub fn zoom_in(&mut self) {
+        self.set_zoom((self.zoom * 2).min(MAX_ZOOM));
+    }
+
+    /// Halves the magnification, back down to the whole flame.
+    pub fn zoom_out(&mut self) {
+        self.set_zoom((self.zoom / 2).max(1));
+    }
+
+    pub fn zoom(&self) -> u16 {
+        self.zoom
+    }
+
+    /// Moves the magnified view by `dx` and `dy` cells, stopping at the edges
+    /// of the flame.
+    pub fn pan(&mut self, dx: i16, dy: i16) {
+        self.viewport.x = self.viewport.x.saturating_add_signed(dx);
+        self.viewport.y = self.viewport.y.saturating_add_signed(dy);
+        self.set_zoom(self.zoom);
+    }
+
+    /// Sizes the viewport for a magnification, keeping it centered on the same
+    /// spot and inside the flame.
+    fn set_zoom(&mut self, zoom: u16) {
+        let viewport = self.viewport;
+        let center_x = viewport.x + viewport.width / 2;
+        let center_y = viewport.y + viewport.height / 2;
+        let flame_width = self.flame_width() as u16;
+        let flame_height = self.flame_height() as u16;
+        let width = flame_width.div_ceil(zoom);
+        let height = (self.display_height as u16)
+            .div_ceil(zoom)
+            .min(flame_height);
+        self.zoom = zoom;
+        self.viewport = Rect {
+            x: center_x.saturating_sub(width / 2).min(flame_width - width),
+            y: center_y
+                .saturating_sub(height / 2)
+                .min(flame_height - height),
+            width,
+            height,
+        };
+    }
+
+    /// Switches between block glyphs, which read better from a distance, and
+    /// the text ramp in use before them.
+    pub fn toggle_blocks(&mut self) {
+        if self.config.char_ramp == CharRamp::Blocks {
+            self.set_char_ramp(self.text_ramp);
+        } else {
+            self.text_ramp = self.config.char_ramp;
+            self.set_char_ramp(CharRamp::Blocks);
+        }
+    }
+
+    /// Switches between the safe glyphs, which every font draws alike, and
+    /// the glyph ramp in use before them.
+    pub fn toggle_safe_glyphs(&mut self) {
+        if self.config.char_ramp == CharRamp::Safe {
+            self.set_char_ramp(self.full_ramp);
+        } else {
+            self.full_ramp = self.config.char_ramp;
+            self.set_char_ramp(CharRamp::Safe);
+        }
+    }
+
+    /// Number of frame columns each cell is drawn across.
+    fn cell_width(&self) -> usize {
+        if self.config.wide { 2 } else { 1 }
+    }
+
+    /// Columns and rows of grid cells behind each frame cell. Braille draws
+    /// two columns of dots per cell. Its four rows of dots are interpolated
+    /// from one grid row instead, since the flame burns to a height in rows
+    /// and would only reach a quarter as high on a taller grid.
+    fn subcells(&self) -> (usize, usize) {
+        if self.config.braille { (2, 1) } else { (1, 1) }
+    }
+
+    /// Number of cells across the frame. With wide cells an odd last column is
+    /// left blank.
+    fn flame_width(&self) -> usize {
+        self.display_width / self.cell_width()
+    }
+
+    /// Number of frame rows taken by the flame, leaving the rest of the frame
+    /// for the reflection.
+    fn flame_height(&self) -> usize {
+        self.display_height.saturating_sub(self.config.reflect_rows)
+    }
+
+    /// How many times taller than wide a grid cell is drawn, taking terminal
+    /// cells as twice as tall as they are wide.
+    fn cell_aspect(&self) -> f32 {
+        let rows_per_cell = self.flame_height().max(1) as f32 / self.height as f32;
+        let columns_per_cell = self.display_width.max(1) as f32 / self.width as f32;
+        2.0 * rows_per_cell / columns_per_cell
+    }
+
+    /// Maps a flame position onto fractional grid coordinates so the corners
+    /// line up.
+    fn grid_position(&self, x: usize, y: usize) -> (f32, f32) {
+        let scale = |i: usize, from: usize, to: usize| {
+            if from <= 1 {
+                0.0
+            } else {
+                i as f32 * (to - 1) as f32 / (from - 1) as f32
+            }
+        };
+        (
+            scale(x, self.flame_width(), self.width),
+            scale(y, self.flame_height(), self.height),
+        )
+    }
+
+    /// Returns the heat to display at a frame position, faded near the sides
+    /// with `edge_falloff`.
+    fn display_heat(&self, x: usize, y: usize) -> u8 {
+        let heat = self.grid_heat(x, y);
+        match self.edge_weights.get(x) {
+            Some(&weight) => (heat as f32 * weight).round() as u8,
+            None => heat,
+        }
+    }
+
+    /// Returns the grid heat at a frame position, bilinearly interpolating the
+    /// grid when it is simulated at a different size.
+    fn grid_heat(&self, x: usize, y: usize) -> u8 {
+        let grid = self.shown_grid();
+        if self.width == self.flame_width() && self.height == self.flame_height() {
+            return to_display(grid[y][x]);
+        }
+
+        let (grid_x, grid_y) = self.grid_position(x, y);
+        let (x0, y0) = (grid_x as usize, grid_y as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (tx, ty) = (grid_x - x0 as f32, grid_y - y0 as f32);
+
+        let lerp = |a: Heat, b: Heat, t: f32| a as f32 + (b as f32 - a as f32) * t;
+        let top = lerp(grid[y0][x0], grid[y0][x1], tx);
+        let bottom = lerp(grid[y1][x0], grid[y1][x1], tx);
+        ((top + (bottom - top) * ty) / HEAT_SCALE as f32).round() as u8
+    }
+
+    /// Packs the 2x4 patch of heat behind a frame position into a braille
+    /// character, dithering the heat so hotter patches get more dots. Returns
+    /// it with the average heat of the dots, scaled by `dim`.
+    fn braille_cell(&self, x: usize, y: usize, dim: f32) -> (char, u8) {
+        // Bit of each dot, by column and then by row
+        const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+        let grid = self.shown_grid();
+        let weight = self.edge_weights.get(x).copied().unwrap_or(1.0);
+        let (columns, rows) = (self.flame_width() * 2, self.flame_height() * 4);
+        let (mut bits, mut dots, mut total_heat) = (0, 0, 0.0);
+        for (dx, column) in DOTS.iter().enumerate() {
+            let grid_x = ((x * 2 + dx) * self.width / columns).min(self.width - 1);
+            for (dy, &bit) in column.iter().enumerate() {
+                // Upside down, the dots within the cell turn over as well
+                let dy = if self.flipped { 3 - dy } else { dy };
+                // Between the centers of the grid rows above and below the dot
+                let grid_y = (((y * 4 + dy) as f32 + 0.5) * self.height as f32 / rows as f32 - 0.5)
+                    .clamp(0.0, (self.height - 1) as f32);
+                let (y0, y1) = (grid_y as usize, (grid_y as usize + 1).min(self.height - 1));
+                let (top, bottom) = (grid[y0][grid_x] as f32, grid[y1][grid_x] as f32);
+                let heat =
+                    (top + (bottom - top) * grid_y.fract()) / HEAT_SCALE as f32 * weight * dim;
+                if dithered_band(heat as u8, 2, x * 2 + dx, y * 4 + dy) > 0 {
+                    bits |= bit;
+                    dots += 1;
+                    total_heat += heat;
+                }
+            }
+        }
+        // A blank braille character would survive trimming in plain output
+        let glyph = match bits {
+            0 => ' ',
+            bits => char::from_u32(0x2800 + bits).unwrap_or(' '),
+        };
+        (glyph, (total_heat / dots.max(1) as f32) as u8)
+    }
+
+    /// Returns the age of the grid cell nearest to a frame position.
+    fn display_age(&self, x: usize, y: usize) -> u8 {
+        let (grid_x, grid_y) = self.grid_position(x, y);
+        self.age_grid[grid_y.round() as usize][grid_x.round() as usize]
+    }
+
+    /// Returns the glyph variant of the grid cell nearest to a frame position.
+    fn display_glyph_variant(&self, x: usize, y: usize) -> u8 {
+        let (grid_x, grid_y) = self.grid_position(x, y);
+        self.glyph_variants[grid_y.round() as usize][grid_x.round() as usize]
+    }
+
+    /// Whether a cell going from one heat to another would be drawn with a
+    /// different glyph band or color.
+    fn band_changed(&self, previous: Heat, next: Heat) -> bool {
+        let (previous, next) = (to_display(previous), to_display(next));
+        let (glyph_bands, color_bands) = (self.char_map.len(), self.config.palette.len());
+        band(previous, glyph_bands) != band(next, glyph_bands)
+            || band(previous, color_bands) != band(next, color_bands)
+    }
+
+    /// Returns the heat at a fractional position along a grid row, clamped to
+    /// the row's ends.
+    fn sample_row(&self, y: usize, x: f32) -> Heat {
+        let (x, x0, x1) = if self.config.wrap {
+            let x = x.rem_euclid(self.width as f32);
+            (x, x as usize % self.width, (x as usize + 1) % self.width)
+        } else {
+            let x = x.clamp(0.0, (self.width - 1) as f32);
+            (x, x as usize, (x as usize + 1).min(self.width - 1))
+        };
+        let t = x - x0 as f32;
+        let row = &self.fire_grid[y];
+        (row[x0] as f32 + (row[x1] as f32 - row[x0] as f32) * t).round() as Heat
+    }
+
+    /// Builds the turbulence field if needed, and lets it wander every few
+    /// frames.
+    fn stir_turbulence(&mut self, dt: f32) {
+        if self.turbulence.is_empty() {
+            let rows = self.height.div_ceil(TURBULENCE_CELL) + 1;
+            let columns = self.width.div_ceil(TURBULENCE_CELL) + 1;
+            self.turbulence = (0..rows)
+                .map(|_| (0..columns).map(|_| self.rng.random()).collect())
+                .collect();
+        } else if self
+            .starts_tick(dt)
+            .is_some_and(|tick| tick.is_multiple_of(TURBULENCE_STIR_TICKS))
+        {
+            for value in self.turbulence.iter_mut().flatten() {
+                let step = self.rng.random_range(-24..=24);
+                *value = value.saturating_add(step).clamp(-127, 127);
+            }
+        }
+    }
+
+    /// Returns the turbulence's sideways nudge in cells at a grid position,
+    /// smoothly interpolated across the coarse field.
+    fn turbulence_at(&self, x: usize, y: usize) -> f32 {
+        if self.turbulence.is_empty() {
+            return 0.0;
+        }
+        let (column, tx) = (
+            x / TURBULENCE_CELL,
+            (x % TURBULENCE_CELL) as f32 / TURBULENCE_CELL as f32,
+        );
+        let (row, ty) = (
+            y / TURBULENCE_CELL,
+            (y % TURBULENCE_CELL) as f32 / TURBULENCE_CELL as f32,
+        );
+        let value = |row: usize, column: usize| self.turbulence[row][column] as f32 / 127.0;
+        let top = value(row, column) + (value(row, column + 1) - value(row, column)) * tx;
+        let bottom =
+            value(row + 1, column) + (value(row + 1, column + 1) - value(row + 1, column)) * tx;
+        self.config.turbulence * (top + (bottom - top) * ty)
+    }
+
+    /// Returns how many cells sideways the heat rising into a grid position
+    /// is carried by the wind, gusts, turbulence and any effect, together.
+    /// Positive values carry it right.
+    pub fn drift_at(&self, x: usize, y: usize) -> f32 {
+        let mut drift = self.wind + self.turbulence_at(x, y);
+        if let Some(effect) = self.config.effect {
+            drift += effect.wind(x, y, self.width, self.height);
+        }
+        drift
+    }
+
+    /// Changes how much of its own heat each cell keeps, within 0 to 1.
+    pub fn nudge_self_weight(&mut self, delta: f32) {
+        self.config.self_weight = (self.config.self_weight + delta).clamp(0.0, 1.0);
+    }
+
+    /// Changes how much heat spreads in from the sides, within 0 to 0.15.
+    pub fn nudge_side_diffusion(&mut self, delta: f32) {
+        self.config.side_diffusion = (self.config.side_diffusion + delta).clamp(0.0, 0.15);
+    }
+
+    /// Changes how hot the flame burns and how high it reaches, within 0 to 1.
+    pub fn nudge_intensity(&mut self, delta: f32) {
+        self.set_intensity(self.config.intensity + delta);
+    }
+
+    /// Sets how fiercely the fire burns, from 0 to 1; see
+    /// [`FireConfig::intensity`]. Values outside that are clamped.
+    ///
+    /// ```
+    /// use fire_in_the_term::App;
+    ///
+    /// let mut app = App::new(40, 20);
+    /// app.set_intensity(1.4);
+    /// assert_eq!(app.config.intensity, 1.0);
+    /// app.nudge_intensity(-1.5);
+    /// assert_eq!(app.config.intensity, 0.0);
+    /// ```
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.config.intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    /// Pushes the flame sideways with a gust that carries on for a moment and
+    /// dies down, letting the wind settle back. Positive impulses blow right.
+    pub fn gust(&mut self, impulse: f32) {
+        self.wind_velocity = (self.wind_velocity + impulse).clamp(-GUST_LIMIT, GUST_LIMIT);
+    }
+
+    /// Sets a steadier wind, within `WIND_LIMIT` either way.
+    pub fn nudge_wind(&mut self, delta: f32) {
+        self.config.wind = (self.config.wind + delta).clamp(-WIND_LIMIT, WIND_LIMIT);
+    }
+
+    /// Switches to another palette, cross-fading over
+    /// `palette_fade_frames` frames.
+    pub fn set_palette(&mut self, palette: Vec<Color>) {
+        if self.config.palette_fade_frames > 0 {
+            self.old_palette = Some(std::mem::replace(&mut self.config.palette, palette));
+            self.transition_progress = 0.0;
+        } else {
+            self.config.palette = palette;
+        }
+        if self.config.align_bands {
+            self.char_map = char_map(&self.config);
+        }
+    }
+
+    /// Switches to the next built-in palette. A custom palette is followed by
+    /// the first one.
+    pub fn cycle_palette(&mut self) {
+        let next = match Preset::find(&self.config.palette) {
+            Some(preset) => preset.next(),
+            None => Preset::ALL[0],
+        };
+        self.set_palette(next.colors().to_vec());
+    }
+
+    /// Switches to the next effect, or back to the plain fire after the last
+    /// one. The flame starts over from cold, so nothing is left burning
+    /// where the last effect lit it.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, effect::Effect};
+    ///
+    /// let mut app = App::new(40, 20).with_seed(2);
+    /// for _ in 0..50 {
+    ///     app.update_fire(TICK_RATE);
+    /// }
+    /// app.cycle_effect();
+    /// assert_eq!(app.config.effect, Some(Effect::Twin));
+    /// assert!(app.grid().iter().flatten().all(|&heat| heat == 0));
+    /// app.cycle_effect();
+    /// app.cycle_effect();
+    /// assert_eq!(app.config.effect, None);
+    /// ```
+    pub fn cycle_effect(&mut self) {
+        self.config.effect = Effect::next(self.config.effect);
+        let cold = vec![vec![0; self.width]; self.height];
+        self.set_grid(&cold);
+        self.bloom_grid.clear();
+        self.ash_grid.clear();
+        self.bursts.clear();
+        // Frames of the last effect would replay it
+        self.frame_history.clear();
+        self.replay_index = None;
+    }
+
+    /// Rolls new tunables with the app's RNG: a random built-in palette and a
+    /// random rise, decay, cooling noise, wind and turbulence. The ranges stay
+    /// close to the defaults, since the flame dies out or fills the frame
+    /// when the rise moves even a few hundredths.
+    pub fn randomize(&mut self) {
+        let preset = Preset::ALL[self.rng.random_range(0..Preset::ALL.len())];
+        self.set_palette(preset.colors().to_vec());
+        self.config.rise_strength = self.rng.random_range(0.495..=0.505);
+        self.config.tip_decay = self.rng.random_range(0.0..=0.3);
+        self.config.cooling_noise_scale = self.rng.random_range(0.0..=1.0);
+        self.config.wind = self.rng.random_range(-0.5..=0.5);
+        self.config.turbulence = self.rng.random_range(0.0..=0.8);
+    }
+
+    /// Switches between the flame and the raw heat values.
+    pub fn toggle_heat_view(&mut self) {
+        self.show_heat = !self.show_heat;
+    }
+
+    /// Shows or hides the wind and turbulence drawn over the flame. Drawing
+    /// it is up to the caller, from [`App::drift_at`].
+    pub fn toggle_wind_view(&mut self) {
+        self.wind_view = !self.wind_view;
+    }
+
+    pub fn wind_view(&self) -> bool {
+        self.wind_view
+    }
+
+    /// Switches between the palette and shades of gray that follow the heat,
+    /// to judge how the heat is spread regardless of the colors. The palette
+    /// itself is left alone, so switching back restores it.
+    pub fn toggle_gray_view(&mut self) {
+        self.gray_view = !self.gray_view;
+    }
+
+    pub fn gray_view(&self) -> bool {
+        self.gray_view
+    }
+
+    /// Turns the flame upside down, or back upright, rotating it about its
+    /// middle row over a few frames. Only the drawing flips; the fire keeps
+    /// burning the same way underneath.
+    pub fn toggle_flip(&mut self) {
+        self.flipped = !self.flipped;
+        // Turning back partway through a flip starts from where it got to
+        self.flip_progress = 1.0 - self.flip_progress;
+    }
+
+    pub fn flipped(&self) -> bool {
+        self.flipped
+    }
+
+    /// Stokes the fire so it leaps up for a moment. Repeated calls stack up to
+    /// a cap.
+    pub fn flare_up(&mut self) {
+        self.flare = (self.flare + 1.0).min(FLARE_CAP);
+    }
+
+    /// Lights a round patch of the fire at full heat, centered on the frame
+    /// cell at (`column`, `row`) and `radius` frame columns across from
+    /// there, taking frame cells as twice as tall as they are wide. Nothing
+    /// is lit outside the flame, nor on a turned or zoomed in frame, where
+    /// the cells drawn don't line up with the grid.
+    ///
+    /// ```
+    /// use fire_in_the_term::App;
+    ///
+    /// let mut app = App::new(40, 20);
+    /// app.paint(20, 5, 4);
+    /// let grid = app.grid();
+    /// assert!(grid[5][20] > 0);
+    /// // Four columns either side, but only two rows up and down
+    /// assert!(grid[5][16] > 0 && grid[5][24] > 0 && grid[5][15] == 0);
+    /// assert!(grid[3][20] > 0 && grid[7][20] > 0 && grid[2][20] == 0);
+    /// ```
+    pub fn paint(&mut self, column: usize, row: usize, radius: usize) {
+        let rows = self.flame_height();
+        if self.config.rotate || self.zoom > 1 || column >= self.display_width || row >= rows {
+            return;
+        }
+        let columns_per_cell = self.display_width as f32 / self.width as f32;
+        let rows_per_cell = rows as f32 / self.height as f32;
+        let row = if self.flipped { rows - 1 - row } else { row };
+        let center = (column as f32 + 0.5, (row as f32 + 0.5) * 2.0);
+        let radius = radius as f32 + 0.5;
+        for (y, heat_row) in self.fire_grid.iter_mut().enumerate() {
+            let dy = (y as f32 + 0.5) * rows_per_cell * 2.0 - center.1;
+            if dy.abs() > radius {
+                continue;
+            }
+            for (x, heat) in heat_row.iter_mut().enumerate() {
+                let dx = (x as f32 + 0.5) * columns_per_cell - center.0;
+                if dx * dx + dy * dy <= radius * radius {
+                    *heat = Heat::MAX;
+                    self.age_grid[y][x] = 0;
+                }
+            }
+        }
+        self.trail_grid.clone_from(&self.fire_grid);
+        self.glow();
+    }
+
+    /// Keeps the fire stoked in proportion to `load`, from 0 for not at all
+    /// to 1 for a steady flare-up of `LOAD_FLARE`, until it is set again.
+    pub fn set_load(&mut self, load: f32) {
+        self.load_flare = load.clamp(0.0, 1.0) * LOAD_FLARE;
+    }
+
+    /// Starts the fire over gently: the base stops being lit so the flame
+    /// burns down on its own, then it catches again.
+    pub fn relight(&mut self) {
+        self.relight_frames = RELIGHT_FRAMES;
+        for layer in &mut self.behind {
+            layer.relight();
+        }
+    }
+
+    /// Puts the base out, or lights it again. While it is out the flame burns
+    /// down and stays dark, until [`App::is_out`] says it has gone.
+    pub fn set_lit(&mut self, lit: bool) {
+        self.lit = lit;
+    }
+
+    /// Puts the flame out, leaving a bed of embers along the base that glows
+    /// at `glow`, from 1 for fresh embers to 0 for cold ones, shimmering and
+    /// throwing up the odd last spark.
+    pub fn smolder(&mut self, glow: f32) {
+        self.lit = false;
+        self.embers = Some(glow.clamp(0.0, 1.0));
+        for layer in &mut self.behind {
+            layer.smolder(glow);
+        }
+    }
+
+    /// Whether the fire has burned down to nothing but the coldest color.
+    pub fn is_out(&self) -> bool {
+        let bands = self.config.palette.len();
+        (self.fire_grid.iter().flatten()).all(|&heat| band(to_display(heat), bands) == 0)
+    }
+
+    /// Shows a short message, such as the new value of a setting, for a
+    /// moment. Drawing it is up to the caller.
+    pub fn show_toast(&mut self, message: impl Into<String>) {
+        self.toast = Some((message.into(), Instant::now()));
+    }
+
+    /// Returns the message from [`App::show_toast`] while it is still up.
+    pub fn toast(&self) -> Option<&str> {
+        self.toast
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < TOAST_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Re-picks every cell's glyph while leaving the heat as it is, so a frozen
+    /// flame keeps glittering. Glyphs picked at random on every draw change
+    /// anyway; this re-picks the ones `diff_render` keeps between frames.
+    pub fn shimmer(&mut self) {
+        if self.config.diff_render {
+            for variant in self.glyph_variants.iter_mut().flatten() {
+                *variant = self.glyph_rng.get_mut().random();
+            }
+        }
+    }
+
+    /// Switches between simulating and looping over the recorded frames.
+    pub fn toggle_replay(&mut self) {
+        self.replay_index = match self.replay_index {
+            None if !self.frame_history.is_empty() => Some(0),
+            _ => None,
+        };
+    }
+
+    /// Advances the animation by one frame: either the next step of the
+    /// simulation, or the next recorded frame while replaying.
+    pub fn advance(&mut self, dt: Duration) {
+        if let Some(index) = self.replay_index {
+            let index = (index + 1) % self.frame_history.len();
+            self.fire_grid.clone_from(&self.frame_history[index]);
+            self.replay_index = Some(index);
+            self.fade_trail();
+            self.glow();
+            return;
+        }
+
+        self.update_fire(dt);
+        let capacity = self.frame_history_capacity();
+        if capacity > 0 {
+            while self.frame_history.len() >= capacity {
+                self.frame_history.pop_front();
+            }
+            self.frame_history.push_back(self.fire_grid.clone());
+        }
+    }
+
+    /// How many frames the history keeps at the current grid size.
+    fn frame_history_capacity(&self) -> usize {
+        let frame_bytes = self.width * self.height * std::mem::size_of::<Heat>();
+        match self.history_memory {
+            Some(bytes) => self.history_capacity.min(bytes / frame_bytes.max(1)),
+            None => self.history_capacity,
+        }
+    }
+
+    /// Updates the fire grid for the next animation frame, `dt` after the
+    /// last one. This simulates heat decay, diffusion, and new heat injection
+    /// at the same speed whatever the frame rate.
+    ///
+    /// A frame timed a hair over a tick takes just the one step, and the
+    /// rest carries over:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE};
+    ///
+    /// let [mut exact, mut late] = [1, 2].map(|_| App::new(40, 20).with_seed(8));
+    /// for _ in 0..50 {
+    ///     exact.update_fire(TICK_RATE);
+    ///     late.update_fire(TICK_RATE.mul_f32(1.001));
+    /// }
+    /// assert_eq!(exact.grid(), late.grid());
+    /// ```
+    ///
+    /// Once it has burned for a while the flame is hottest at its base:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, HEAT_SCALE, TICK_RATE};
+    ///
+    /// for seed in 0..5 {
+    ///     let mut app = App::new(60, 30).with_seed(seed);
+    ///     for _ in 0..200 {
+    ///         app.update_fire(TICK_RATE);
+    ///     }
+    ///     let grid = app.grid();
+    ///     let mean =
+    ///         |row: &[_]| row.iter().map(|&heat| heat as f64).sum::<f64>() / row.len() as f64;
+    ///     assert!(mean(&grid[29]) > mean(&grid[0]));
+    ///     assert!(grid.iter().flatten().any(|&heat| heat > 0));
+    ///     assert!(grid.iter().flatten().all(|&heat| heat <= 255 * HEAT_SCALE));
+    /// }
+    /// ```
+    pub fn update_fire(&mut self, dt: Duration) {
+        self.sync_layers();
+        for (depth, layer) in (1..).zip(&mut self.behind) {
+            layer.update_fire(dt.mul_f32(LAYER_SPEED.powi(depth)));
+        }
+        let substeps = self.config.substeps.max(1) as f32;
+        let ticks = dt.as_secs_f32() / TICK_RATE.as_secs_f32();
+        self.pending_steps = (self.pending_steps + ticks * substeps).min(MAX_STEP_TICKS * substeps);
+        // Long frames are simulated as several full steps and the remainder,
+        // unless it is too short to be worth a step of its own
+        while self.pending_steps >= 1.0 {
+            self.step(1.0 / substeps);
+            self.pending_steps -= 1.0;
+        }
+        if self.pending_steps >= MIN_PARTIAL_STEP {
+            self.step(self.pending_steps / substeps);
+            self.pending_steps = 0.0;
+        }
+        self.watch_saturation(dt.as_secs_f32() / TICK_RATE.as_secs_f32());
+    }
+
+    /// Keeps count of how long the mean heat has stayed near white, and with
+    /// `auto_dampen` cools the fire harder for as long as it is blown out.
+    fn watch_saturation(&mut self, ticks: f32) {
+        let cells = (self.width * self.height).max(1);
+        let total: u64 = self
+            .fire_grid
+            .iter()
+            .flatten()
+            .map(|&heat| heat as u64)
+            .sum();
+        let mean = total as f32 / cells as f32 / HEAT_SCALE as f32;
+        if mean >= SATURATION_HEAT {
+            self.saturated_ticks += ticks;
+        } else {
+            self.saturated_ticks = 0.0;
+        }
+        if self.blown_out() && self.config.auto_dampen {
+            self.damping = (self.damping + DAMPING_RATE * ticks).min(MAX_DAMPING);
+        }
+    }
+
+    /// Whether the fire has burned near white all over for a few seconds in a
+    /// row, which settings such as a rise or self weight too strong for the
+    /// decay lead to. With `auto_dampen` the fire cools harder until it
+    /// recovers, and keeps that extra cooling while its config stays.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig {
+    ///     rise_strength: 1.2,
+    ///     self_weight: 0.5,
+    ///     auto_dampen: true,
+    ///     ..FireConfig::default()
+    /// };
+    /// let mut app = App::new(40, 20).with_config(config).with_seed(1);
+    /// let mut blown_out = false;
+    /// for _ in 0..1000 {
+    ///     app.update_fire(TICK_RATE);
+    ///     blown_out |= app.blown_out();
+    /// }
+    /// assert!(blown_out);
+    /// // Cooled harder until it burns like a fire again
+    /// assert!(!app.blown_out());
+    /// ```
+    pub fn blown_out(&self) -> bool {
+        self.saturated_ticks >= SATURATION_TICKS
+    }
+
+    /// Sets up the buffers a step works in for the grid's current size, which
+    /// the next update would otherwise do. Once they fit, and with the
+    /// default settings, `update_fire` allocates nothing, so call this after
+    /// a resize to keep the allocation out of the frames that follow.
+    ///
+    /// ```rust,standalone_crate
+    /// use fire_in_the_term::{App, TICK_RATE};
+    /// use std::{
+    ///     alloc::{GlobalAlloc, Layout, System},
+    ///     sync::atomic::{AtomicUsize, Ordering},
+    /// };
+    ///
+    /// static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// struct Counting;
+    ///
+    /// unsafe impl GlobalAlloc for Counting {
+    ///     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ///         ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ///         unsafe { System.alloc(layout) }
+    ///     }
+    ///
+    ///     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ///         unsafe { System.dealloc(ptr, layout) }
+    ///     }
+    /// }
+    ///
+    /// #[global_allocator]
+    /// static GLOBAL: Counting = Counting;
+    ///
+    /// fn main() {
+    ///     let mut app = App::new(80, 24).with_seed(1);
+    ///     app.reserve();
+    ///     let before = ALLOCATIONS.load(Ordering::Relaxed);
+    ///     for _ in 0..500 {
+    ///         app.update_fire(TICK_RATE);
+    ///     }
+    ///     assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before);
+    /// }
+    /// ```
+    pub fn reserve(&mut self) {
+        let fits = self.spare_grid.len() == self.height
+            && self.spare_grid.iter().all(|row| row.len() == self.width);
+        if !fits {
+            self.spare_grid = vec![vec![0; self.width]; self.height];
+        }
+        self.ignited.resize(self.width, false);
+        self.diffusion_weights.reserve(self.config.diffusion_radius);
+        self.rolls.reserve(self.width);
+        for sums in &mut self.side_sums {
+            sums.resize(self.width, 0.0);
+        }
+    }
+
+    /// How many whole ticks of `TICK_RATE` have been simulated so far.
+    pub fn tick_count(&self) -> u64 {
+        // Substeps add up to a tick only to within rounding
+        (self.ticks + TICK_SLACK) as u64
+    }
+
+    /// Fast-forwards the fire from where it is to `target_ticks` by
+    /// simulating a tick at a time. A seeded app seeked from the start always
+    /// ends up the same. The fire can't be run backwards, so a target already
+    /// passed changes nothing.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE};
+    ///
+    /// let mut stepped = App::new(40, 20).with_seed(9);
+    /// for _ in 0..100 {
+    ///     stepped.update_fire(TICK_RATE);
+    /// }
+    /// let mut seeked = App::new(40, 20).with_seed(9);
+    /// seeked.seek(100);
+    /// assert_eq!(seeked.tick_count(), 100);
+    /// assert_eq!(seeked.grid(), stepped.grid());
+    /// ```
+    pub fn seek(&mut self, target_ticks: u64) {
+        while self.tick_count() < target_ticks {
+            self.update_fire(TICK_RATE);
+        }
+    }
+
+    /// Brings the fires behind this one in line with `layers` and everything
+    /// they copy from this one: its size, settings, colors and whether it is
+    /// lit.
+    fn sync_layers(&mut self) {
+        let count = self.config.layers.saturating_sub(1);
+        self.behind.truncate(count);
+        let size = self.display_size();
+        for depth in 1..=count {
+            let config = self.layer_config(depth as i32);
+            match self.behind.get_mut(depth - 1) {
+                Some(layer) => {
+                    if layer.config != config {
+                        layer.set_config(config);
+                    }
+                    if layer.display_size() != size {
+                        layer.resize(size.0, size.1);
+                    }
+                }
+                None => {
+                    let seed = self.seed.wrapping_add(depth as u64);
+                    let layer = App::new(size.0, size.1).with_config(config).with_seed(seed);
+                    self.behind.push(layer);
+                }
+            }
+            let layer = &mut self.behind[depth - 1];
+            layer.color_mode = self.color_mode;
+            layer.lit = self.lit;
+        }
+    }
+
+    /// The settings of the fire `depth` layers behind this one: dimmer the
+    /// further back it is, and without the snow and ash, which fall in front.
+    fn layer_config(&self, depth: i32) -> FireConfig {
+        FireConfig {
+            layers: 1,
+            brightness: self.config.brightness * LAYER_BRIGHTNESS.powi(depth),
+            contrast: self.config.contrast * LAYER_CONTRAST.powi(depth),
+            ash: None,
+            falling: None,
+            ..self.config.clone()
+        }
+    }
+
+    /// Returns the first whole tick reached during a step of `dt` ticks from
+    /// now, if the step reaches one.
+    fn starts_tick(&self, dt: f32) -> Option<u64> {
+        let tick = self.ticks.ceil();
+        (tick < self.ticks + dt as f64).then_some(tick as u64)
+    }
+
+    /// Simulates `dt` ticks, at most one. Shorter steps only move each cell
+    /// that fraction of the way to where a full tick would take it.
+    #[allow(clippy::needless_range_loop)]
+    fn step(&mut self, dt: f32) {
+        // Work into a separate buffer to avoid modifying the current grid
+        // while calculating new values based on its current state.
+        self.reserve();
+        let mut next_grid = mem::take(&mut self.spare_grid);
+        for row in &mut next_grid {
+            row.fill(0);
+        }
+
+        // Short grids rise higher and cool slower so the flame still shows
+        let (base_rise_strength, mut decay_scale) = if self.compact {
+            (
+                self.config.compact_rise_strength,
+                self.config.compact_decay_scale,
+            )
+        } else {
+            (self.config.rise_strength, 1.0)
+        };
+
+        decay_scale *= (1.5 - self.config.intensity) * (1.0 + self.damping);
+
+        // A flare-up temporarily strengthens the rise and the ignition
+        let flare = self.flare + self.load_flare;
+        let rise_strength = base_rise_strength * (1.0 + 0.3 * flare);
+        let ignition_boost = 0.1 * flare as f64;
+        // The pulse follows the clock rather than the frame count so its beat
+        // holds at any frame rate
+        let pulse = if self.config.pulse_bpm > 0.0 {
+            let beats = self.started.elapsed().as_secs_f64() * self.config.pulse_bpm as f64 / 60.0;
+            1.0 - PULSE_DEPTH * (0.5 - 0.5 * (beats * std::f64::consts::TAU).sin())
+        } else {
+            1.0
+        };
+        self.flare = (self.flare - dt / FLARE_FRAMES).max(0.0);
+        let unlit = self.relight_frames > 0.0 || !self.lit;
+        self.relight_frames = (self.relight_frames - dt).max(0.0);
+
+        let mut target_wind = self.config.wind;
+        if self.config.auto_wind {
+            self.wind_phase =
+                (self.wind_phase + dt * TAU / self.config.wind_period).rem_euclid(TAU);
+            target_wind += self.config.wind_amplitude * self.wind_phase.sin();
+        }
+        if self.config.wander > 0.0 {
+            let swings = dt * TICK_RATE.as_secs_f32() * self.config.wander / 60.0;
+            self.wander_phase = (self.wander_phase + swings * TAU).rem_euclid(TAU);
+            self.base_center = 0.5 + WANDER_REACH * self.wander_phase.sin();
+        } else {
+            self.base_center = 0.5;
+        }
+
+        // Gusts carry the wind along as they die down, while it keeps easing
+        // back towards the target
+        self.wind += self.wind_velocity * dt;
+        self.wind_velocity *= (1.0 - GUST_DAMPING * dt).max(0.0);
+        self.wind += (target_wind - self.wind) * (WIND_SMOOTHING * dt);
+
+        let mut diffusion_weights = mem::take(&mut self.diffusion_weights);
+        diffusion_weights.clear();
+        diffusion_weights.extend(side_weights(
+            self.config.diffusion_radius,
+            self.config.side_diffusion,
+        ));
+        let burst_boost = self.update_bursts(dt);
+        let draft = self.config.draft;
+        let cooling_noise = self.config.cooling_noise_scale;
+        if cooling_noise > 0.0 && self.cooling_map.is_empty() {
+            self.cooling_map = value_noise(&mut self.rng, self.width, self.height);
+        }
+
+        if self.config.turbulence > 0.0 {
+            self.stir_turbulence(dt);
+        }
+
+        // Step 1: Heat propagation (upwards diffusion) and decay
+        // Every row but the bottom one is worked out from the last frame, by
+        // default from the second-to-last row up to the first row (top).
+        // Rows never read from each other's new heat, so the order only
+        // decides which random numbers land on which cell.
+        //
+        // Each row draws all its cells' random numbers before working any of
+        // them out, in the same order the cells would draw them one by one,
+        // so a seeded fire comes out the same either way. Rows nothing pushes
+        // sideways or cools unevenly are then worked out in straight runs
+        // over whole rows, which the compiler turns into SIMD; the rest go
+        // cell by cell.
+        let plain_rows = self.wind == 0.0
+            && self.turbulence.is_empty()
+            && self.config.effect.is_none()
+            && burst_boost.is_empty()
+            && draft <= 0.0
+            && cooling_noise <= 0.0
+            && !self.config.wrap;
+        let mut rolls = mem::take(&mut self.rolls);
+        let [mut left_sums, mut right_sums] = mem::take(&mut self.side_sums);
+        for row in 0..self.height - 1 {
+            let y = match self.config.update_order {
+                UpdateOrder::BottomUp => self.height - 2 - row,
+                UpdateOrder::TopDown => row,
+            };
+            // Apply decay: Higher decay to keep the flame localized, scaled up
+            // towards the top so the tips taper off
+            let tip_factor = 1.0 + self.config.tip_decay * (1.0 - y as f32 / self.height as f32);
+            rolls.clear();
+            for x in 0..self.width {
+                let mut cell_rng;
+                let rng: &mut dyn RngCore = if self.config.cell_seeding {
+                    cell_rng =
+                        CellRng::new(self.seed, RISE_STREAM, x, self.height - 1 - y, self.steps);
+                    &mut cell_rng
+                } else {
+                    &mut self.rng
+                };
+                rolls.draw(rng, tip_factor, decay_scale);
+            }
+            if self.config.coherent_flicker {
+                // The numbers are still drawn so the decay falls the same way
+                let time = self.ticks as f32 * self.config.flicker_speed;
+                let y = (self.height - 1 - y) as f32 / self.config.flicker_scale;
+                for (x, (fluctuation, up)) in
+                    rolls.fluctuation.iter_mut().zip(&mut rolls.up).enumerate()
+                {
+                    let x = x as f32 / self.config.flicker_scale;
+                    let flicker = flicker_noise(self.seed, x, y, time);
+                    *fluctuation = (flicker.abs() * FLICKER_DEPTH * HEAT_SCALE as f32) as Heat;
+                    *up = flicker >= 0.0;
+                }
+            }
+
+            if plain_rows {
+                let row = &self.fire_grid[y];
+                side_sums(row, &diffusion_weights, &mut left_sums, &mut right_sums);
+                rise_row(
+                    &mut next_grid[y],
+                    row,
+                    &self.fire_grid[y + 1],
+                    &rolls,
+                    (&left_sums, &right_sums),
+                    rise_strength,
+                    self.config.self_weight,
+                );
+                continue;
+            }
+
+            for x in 0..self.width {
+                let current_heat = self.fire_grid[y][x];
+                let drift = self.drift_at(x, y);
+                let below_heat = if drift == 0.0 {
+                    self.fire_grid[y + 1][x]
+                } else {
+                    // Wind and turbulence carry the rising heat sideways
+                    self.sample_row(y + 1, x as f32 - drift)
+                };
+
+                // Base heat from the cell directly below, but heavily reduced to limit upward movement.
+                // Combined with a portion of the current cell's heat to create a more "flickering in place" effect.
+                let rise_strength = burst_boost
+                    .get(x)
+                    .map_or(rise_strength, |boost| rise_strength * boost);
+                let rising_heat = (below_heat as f32 * rise_strength) as Heat;
+                let own_heat = (current_heat as f32 * self.config.self_weight) as Heat;
+                let mut new_cell_heat = rising_heat.saturating_add(own_heat);
+
+                // Add small contributions from side neighbors (diffusion)
+                let row = &self.fire_grid[y];
+                let (mut left_heat, mut right_heat) = (0.0, 0.0);
+                for (distance, &weight) in (1..).zip(&diffusion_weights) {
+                    let (left, right) = if self.config.wrap {
+                        let distance = distance % self.width;
+                        (
+                            Some((x + self.width - distance) % self.width),
+                            Some((x + distance) % self.width),
+                        )
+                    } else {
+                        (x.checked_sub(distance), Some(x + distance))
+                    };
+                    if let Some(&left) = left.and_then(|left| row.get(left)) {
+                        left_heat += left as f32 * weight;
+                    }
+                    if let Some(&right) = right.and_then(|right| row.get(right)) {
+                        right_heat += right as f32 * weight;
+                    }
+                }
+                if draft > 0.0 {
+                    // The draft pulls heat in from the sides: cells take more
+                    // from their outer neighbors and less from their inner ones
+                    let half_width = self.width as f32 / 2.0;
+                    let pull = draft * ((x as f32 + 0.5 - half_width) / half_width);
+                    left_heat *= 1.0 - pull;
+                    right_heat *= 1.0 + pull;
+                }
+                new_cell_heat = new_cell_heat
+                    .saturating_add(left_heat as Heat)
+                    .saturating_add(right_heat as Heat);
+
+                let mut decay_amount = rolls.decay[x];
+                if cooling_noise > 0.0 {
+                    let cooling_y = (y + self.ticks as usize) % self.cooling_map.len();
+                    let cooling = cooling_noise * self.cooling_map[cooling_y][x];
+                    decay_amount =
+                        decay_amount.saturating_add((cooling * HEAT_SCALE as f32) as Heat);
+                }
+                let decayed_heat = new_cell_heat.saturating_sub(decay_amount + ROUNDING_LOSS);
+
+                // Add random fluctuation for flickering. More intense fluctuation.
+                next_grid[y][x] = if rolls.up[x] {
+                    decayed_heat.saturating_add(rolls.fluctuation[x])
+                } else {
+                    decayed_heat.saturating_sub(rolls.fluctuation[x])
+                };
+            }
+        }
+        self.rolls = rolls;
+        self.side_sums = [left_sums, right_sums];
+
+        // Step 2: Inject new heat at the bottom (logs/fire source)
+        // This is where new flames are "born"
+        // The very bottom row, unless the source is raised above it
+        let log_row = (self.height - 1).saturating_sub(self.config.injection_row_offset);
+        let mut ignited = mem::take(&mut self.ignited);
+        ignited.fill(false);
+        for x in 0..self.width {
+            // Introduce new random heat. Make it more likely in the center to shape the flame.
+            let distance_from_center = (x as f32 - self.width as f32 * self.base_center).abs();
+            let center_bias = match self.config.effect {
+                // Effects with sources of their own center on those instead
+                Some(effect) => effect.source_bias(x, self.width),
+                // 1.0 at center, 0.0 at edges, or beyond them for a wandering base
+                None => (1.0 - (distance_from_center / (self.width as f32 / 2.0))).max(0.0),
+            };
+            let curve = self
+                .config
+                .base_curve
+                .weight(center_bias, self.config.base_exponent);
+            let mut chance = (curve as f64 + ignition_boost) * pulse;
+            if draft > 0.0 {
+                // Air drawn in at the sides starves the outer columns
+                chance *= (1.0 - draft * (1.0 - center_bias).powi(2)) as f64;
+            }
+            let profile = &self.config.injection_profile;
+            if !profile.is_empty() {
+                chance *= profile[x * profile.len() / self.width] as f64;
+            }
+
+            let mut cell_rng;
+            let rng: &mut dyn RngCore = if self.config.cell_seeding {
+                cell_rng = CellRng::new(
+                    self.seed,
+                    IGNITION_STREAM,
+                    x,
+                    self.height - 1 - log_row,
+                    self.steps,
+                );
+                &mut cell_rng
+            } else {
+                &mut self.rng
+            };
+            if !unlit && rng.random_bool(chance.min(1.0)) {
+                // Use higher power for even sharper center concentration
+                // Add significant heat if biased and random chance hits
+                let target = rng.random_range(200 * HEAT_SCALE..=255 * HEAT_SCALE);
+                let build_frames = self.config.ignition_build_frames;
+                next_grid[log_row][x] = if build_frames > 0 {
+                    // Close part of the gap to the target, so the base only
+                    // gets hot where it keeps being hit
+                    let previous = self.fire_grid[log_row][x] as f32;
+                    (previous + (target as f32 - previous) / build_frames as f32) as Heat
+                } else {
+                    target
+                };
+                ignited[x] = true;
+            } else {
+                // Let the embers left from the last frame die down gradually
+                // where they aren't reignited
+                let cooling = rng.random_range(5 * HEAT_SCALE..=10 * HEAT_SCALE);
+                next_grid[log_row][x] = self.fire_grid[log_row][x].saturating_sub(cooling);
+            }
+        }
+        // Bursts keep their spot of the base at full heat
+        for (x, &boost) in burst_boost.iter().enumerate() {
+            if boost > 1.0 && !unlit {
+                next_grid[log_row][x] = Heat::MAX;
+                ignited[x] = true;
+            }
+        }
+        // A smoldering base glows unevenly in slow waves along the embers
+        if let Some(glow) = self.embers {
+            let phase = self.ticks as f32 * EMBER_PULSE;
+            for (x, heat) in next_grid[log_row].iter_mut().enumerate() {
+                let shimmer = 0.7
+                    + 0.3 * (phase + x as f32 * 0.9).sin() * (phase * 0.7 + x as f32 * 0.3).cos();
+                *heat = (glow * shimmer * EMBER_HEAT * HEAT_SCALE as f32) as Heat;
+                if self.rng.random_bool(EMBER_SPARK_CHANCE * glow as f64) {
+                    *heat = Heat::MAX;
+                }
+            }
+        }
+        // Regions of a burn mask that sit on unmarked cells have no base
+        // below to rise from, so their bottom edge is lit as well
+        if !self.burnable.is_empty() && !unlit {
+            for y in 0..log_row {
+                for x in 0..self.width {
+                    if self.burnable[y][x]
+                        && !self.burnable[y + 1][x]
+                        && self.rng.random_bool(MASK_IGNITION_CHANCE * pulse)
+                    {
+                        next_grid[y][x] =
+                            self.rng.random_range(200 * HEAT_SCALE..=255 * HEAT_SCALE);
+                    }
+                }
+            }
+        }
+        // Effects lit away from the base, like the core of a fireball
+        if let Some(effect) = self.config.effect.filter(|_| !unlit) {
+            let aspect = self.cell_aspect();
+            for (y, row) in next_grid.iter_mut().enumerate() {
+                for (x, heat) in row.iter_mut().enumerate() {
+                    let chance = effect.ignition(x, y, self.width, self.height, aspect) * pulse;
+                    if chance > 0.0 && self.rng.random_bool(chance.min(1.0)) {
+                        *heat = self.rng.random_range(200 * HEAT_SCALE..=255 * HEAT_SCALE);
+                    }
+                }
+            }
+        }
+
+        // Step 3: Cover only part of the change in a short step or a viscous
+        // flame, rounding randomly so small changes still add up over several
+        // steps
+        let blend = dt * (1.0 - self.config.viscosity);
+        if blend < 1.0 {
+            for (next_row, row) in next_grid.iter_mut().zip(&self.fire_grid) {
+                for (next, &current) in next_row.iter_mut().zip(row) {
+                    let partial = current as f32 + (*next as f32 - current as f32) * blend;
+                    // Rounded up by comparing the fraction rather than adding
+                    // to the heat, which rounds large 16-bit heats up to the
+                    // next whole number even with nothing to add
+                    let (whole, fraction) = (partial.floor(), partial.fract());
+                    let round_up = self.rng.random::<f32>() >= 1.0 - fraction;
+                    *next = whole as Heat + round_up as Heat;
+                }
+            }
+        }
+
+        // Step 4: Keep every cell within the configured heat range
+        let (floor, ceil) = (self.config.floor_heat, self.config.ceil_heat);
+        if floor > 0 || ceil < 255 {
+            let (floor, ceil) = (floor as Heat * HEAT_SCALE, ceil as Heat * HEAT_SCALE);
+            for heat in next_grid.iter_mut().flatten() {
+                *heat = (*heat).clamp(floor, ceil);
+            }
+        }
+
+        // Step 5: Put out every cell outside the flame's shape, above its
+        // height cap or below the source
+        let capped_rows =
+            self.height - (self.config.max_height * self.height as f32).ceil() as usize;
+        for row in next_grid.iter_mut().take(capped_rows) {
+            row.fill(0);
+        }
+        for row in next_grid.iter_mut().skip(log_row + 1) {
+            row.fill(0);
+        }
+        if !self.burnable.is_empty() {
+            for (row, burnable_row) in next_grid.iter_mut().zip(&self.burnable) {
+                for (heat, &burnable) in row.iter_mut().zip(burnable_row) {
+                    if !burnable {
+                        *heat = 0;
+                    }
+                }
+            }
+        }
+        if let Some(shape) = self.config.shape {
+            for (y, row) in next_grid.iter_mut().enumerate() {
+                for (x, heat) in row.iter_mut().enumerate() {
+                    if !shape.contains(x, y, self.width, self.height) {
+                        *heat = 0;
+                    }
+                }
+            }
+        }
+        if let Some(effect) = self.config.effect {
+            let aspect = self.cell_aspect();
+            for (y, row) in next_grid.iter_mut().enumerate() {
+                for (x, heat) in row.iter_mut().enumerate() {
+                    let reach = effect.reach(x, y, self.width, self.height, aspect);
+                    if reach < 1.0 {
+                        *heat = (*heat as f32 * reach) as Heat;
+                    }
+                }
+            }
+        }
+
+        if self.config.falling.is_some() || !self.specks.is_empty() {
+            self.fall(&mut next_grid, dt);
+        }
+
+        // Step 6: Age cells that keep their heat, and restart the age of cells
+        // that were just ignited
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (previous, next) = (self.fire_grid[y][x], next_grid[y][x]);
+                let spiked = next >= previous.saturating_add(IGNITION_SPIKE);
+                let age = &mut self.age_grid[y][x];
+                if next == 0 || spiked || (y == log_row && ignited[x]) {
+                    *age = 0;
+                } else {
+                    *age = age.saturating_add(1);
+                }
+            }
+        }
+
+        if self.config.ash.is_some() {
+            self.settle_ash(&next_grid, dt);
+        }
+
+        // Step 7: Re-pick the glyphs of cells that will look different
+        if self.config.diff_render {
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    if self.band_changed(self.fire_grid[y][x], next_grid[y][x]) {
+                        self.glyph_variants[y][x] = self.glyph_rng.get_mut().random();
+                    }
+                }
+            }
+        }
+
+        self.spare_grid = mem::replace(&mut self.fire_grid, next_grid);
+        self.ignited = ignited;
+        self.diffusion_weights = diffusion_weights;
+        if let Some(post_process) = &mut self.post_process {
+            post_process(&mut self.fire_grid);
+        }
+        self.fade_trail();
+        self.glow();
+        if self.old_palette.is_some() {
+            self.transition_progress += 1.0 / self.config.palette_fade_frames.max(1) as f32;
+            if self.transition_progress >= 1.0 {
+                self.old_palette = None;
+            }
+        }
+        self.flip_progress = (self.flip_progress + dt / FLIP_FRAMES).min(1.0);
+        self.ticks += dt as f64;
+        self.steps += 1;
+    }
+
+    /// Starts the spontaneous bursts due by now and ages the running ones.
+    /// Returns how much each column's rise is strengthened, or nothing while
+    /// no burst is running.
+    fn update_bursts(&mut self, dt: f32) -> Vec<f32> {
+        let rate = self.config.flare_rate;
+        if rate <= 0.0 {
+            self.bursts.clear();
+            self.next_burst = None;
+            return Vec::new();
+        }
+
+        self.bursts.retain_mut(|burst| {
+            burst.remaining -= dt;
+            burst.remaining > 0.0
+        });
+        // Bursts come at random, `60 / flare_rate` seconds apart on average,
+        // so the gaps between them are exponentially distributed
+        let mean_ticks = 60.0 / rate as f64 / TICK_RATE.as_secs_f64();
+        let gap = |rng: &mut ChaCha12Rng| -mean_ticks * (1.0 - rng.random::<f64>()).ln();
+        let mut next_burst = match self.next_burst {
+            Some(next_burst) => next_burst,
+            None => self.ticks + gap(&mut self.rng),
+        };
+        while next_burst <= self.ticks {
+            self.bursts.push(Burst {
+                column: self.rng.random_range(0..self.width),
+                remaining: BURST_FRAMES,
+            });
+            next_burst += gap(&mut self.rng);
+        }
+        self.next_burst = Some(next_burst);
+
+        if self.bursts.is_empty() {
+            return Vec::new();
+        }
+        let mut boost = vec![1.0_f32; self.width];
+        for burst in &self.bursts {
+            let start = burst.column.saturating_sub(BURST_RADIUS);
+            let end = (burst.column + BURST_RADIUS).min(self.width - 1);
+            for (x, boost) in boost.iter_mut().enumerate().take(end + 1).skip(start) {
+                let closeness = 1.0 - x.abs_diff(burst.column) as f32 / (BURST_RADIUS + 1) as f32;
+                let strength = closeness * burst.remaining / BURST_FRAMES;
+                *boost = boost.max(1.0 + BURST_RISE * strength);
+            }
+        }
+        boost
+    }
+
+    /// Keeps ash ready wherever a cell burns hot, and lets it blow away where
+    /// none has since.
+    fn settle_ash(&mut self, next_grid: &[Vec<Heat>], dt: f32) {
+        if self.ash_grid.is_empty() {
+            self.ash_grid = vec![vec![0.0; self.width]; self.height];
+        }
+        for (ash_row, row) in self.ash_grid.iter_mut().zip(next_grid) {
+            for (ash, &heat) in ash_row.iter_mut().zip(row) {
+                *ash = if to_display(heat) >= ASH_SOURCE_HEAT {
+                    ASH_FRAMES
+                } else {
+                    (*ash - dt).max(0.0)
+                };
+            }
+        }
+    }
+
+    /// The ash color at a frame position, if a cell that burned hot a moment
+    /// ago has all but gone out there.
+    fn ash(&self, heat: u8, x: usize, y: usize) -> Option<Color> {
+        let color = self.config.ash?;
+        if heat >= ASH_MAX_HEAT || self.ash_grid.is_empty() || self.show_heat {
+            return None;
+        }
+        let (grid_x, grid_y) = self.grid_position(x, y);
+        (self.ash_grid[grid_y.round() as usize][grid_x.round() as usize] > 0.0).then_some(color)
+    }
+
+    /// Moves the falling specks down and sideways with the wind, starting new
+    /// ones at the top while something is set to fall. A speck that reaches a
+    /// hot cell melts or burns up there, and snow cools the cell as it does.
+    fn fall(&mut self, next_grid: &mut [Vec<Heat>], dt: f32) {
+        let (width, height) = (self.width as f32, self.height as f32);
+        let rng = &mut self.rng;
+        self.specks.retain_mut(|speck| {
+            speck.y += speck.speed * dt;
+            speck.x += (0.5 * self.wind + rng.random_range(-0.3..=0.3)) * dt;
+            if speck.y >= height || !(0.0..width).contains(&speck.x) {
+                return false;
+            }
+            let heat = &mut next_grid[speck.y as usize][speck.x as usize];
+            if to_display(*heat) < SPECK_MELT_HEAT {
+                return true;
+            }
+            if self.config.falling == Some(Falling::Snow) {
+                *heat = heat.saturating_sub(SNOW_COOLING);
+            }
+            false
+        });
+        if self.config.falling.is_none() {
+            return;
+        }
+        for x in 0..self.width {
+            if self.rng.random_bool((SPECK_CHANCE * dt as f64).min(1.0)) {
+                self.specks.push(Speck {
+                    x: x as f32 + self.rng.random::<f32>(),
+                    y: 0.0,
+                    speed: self.rng.random_range(SPECK_SPEEDS),
+                });
+            }
+        }
+    }
+
+    /// The glyph and color of a speck falling through a frame position.
+    fn speck(&self, x: usize, y: usize) -> Option<(char, Color)> {
+        if self.specks.is_empty() || self.show_heat {
+            return None;
+        }
+        let (grid_x, grid_y) = self.grid_position(x, y);
+        let (grid_x, grid_y) = (grid_x.round(), grid_y.round());
+        let speck = match self.config.falling {
+            Some(Falling::Ash) => ('.', Color::Rgb(128, 128, 128)),
+            // Snow, or whatever is left of it after falling was turned off
+            _ => ('*', Color::Rgb(240, 240, 255)),
+        };
+        (self.specks.iter())
+            .any(|speck| speck.x.floor() == grid_x && speck.y.floor() == grid_y)
+            .then_some(speck)
+    }
+
+    /// Blends the new grid into the blur trail, keeping whichever is hotter of
+    /// each cell and the faded heat drawn there last frame.
+    fn fade_trail(&mut self) {
+        let fade = self.config.blur_trail;
+        if fade <= 0.0 {
+            return;
+        }
+        for (trail_row, row) in self.trail_grid.iter_mut().zip(&self.fire_grid) {
+            for (trail, &heat) in trail_row.iter_mut().zip(row) {
+                *trail = heat.max((*trail as f32 * fade) as Heat);
+            }
+        }
+    }
+
+    /// The grid drawn in place of the simulated one, with the blur trail and
+    /// bloom applied.
+    fn shown_grid(&self) -> &[Vec<Heat>] {
+        if !self.bloom_grid.is_empty() {
+            &self.bloom_grid
+        } else if self.config.blur_trail > 0.0 {
+            &self.trail_grid
+        } else {
+            &self.fire_grid
+        }
+    }
+
+    /// Works out the bloom: the heat above `BLOOM_THRESHOLD` is blurred
+    /// across and then down, and added back onto the drawn heat scaled by
+    /// `bloom`, so the hottest cores light up a halo around them.
+    fn glow(&mut self) {
+        let strength = self.config.bloom;
+        if strength <= 0.0 {
+            self.bloom_grid.clear();
+            return;
+        }
+        let grid = if self.config.blur_trail > 0.0 {
+            &self.trail_grid
+        } else {
+            &self.fire_grid
+        };
+        let threshold = BLOOM_THRESHOLD as Heat * HEAT_SCALE;
+        let (width, height) = (self.width as isize, self.height as isize);
+        let blur = |sample: &dyn Fn(isize) -> f32, at: isize, len: isize| {
+            (BLOOM_KERNEL.iter().enumerate())
+                .map(|(i, weight)| {
+                    let offset = at + i as isize - BLOOM_KERNEL.len() as isize / 2;
+                    if (0..len).contains(&offset) {
+                        weight * sample(offset)
+                    } else {
+                        0.0
+                    }
+                })
+                .sum::<f32>()
+        };
+        let across: Vec<Vec<f32>> = (grid.iter())
+            .map(|row| {
+                let bright = |x: isize| row[x as usize].saturating_sub(threshold) as f32;
+                (0..width).map(|x| blur(&bright, x, width)).collect()
+            })
+            .collect();
+        self.bloom_grid = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let glow = blur(&|y: isize| across[y as usize][x as usize], y, height);
+                        let heat = grid[y as usize][x as usize];
+                        heat.saturating_add((glow * strength).min(Heat::MAX as f32) as Heat)
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Picks the glyph to draw for a heat value.
+    fn glyph(&self, heat: u8, x: usize, y: usize) -> char {
+        if self.show_heat {
+            // Show the high nibble of the heat value as a hex digit
+            return char::from_digit((heat >> 4) as u32, 16).unwrap_or('?');
+        }
+
+        if self.config.glyph_stability || heat < self.config.shimmer_threshold {
+            return self.char_for_heat(heat);
+        }
+        let variants = &self.char_map[band(heat, self.char_map.len())];
+        let variant_index = if self.config.diff_render {
+            self.display_glyph_variant(x, y) as usize % variants.len()
+        } else {
+            match self.config.glyph_variety {
+                GlyphVariety::Random => self.glyph_rng.borrow_mut().random_range(0..variants.len()),
+                GlyphVariety::Hashed => {
+                    let cell = (y as u64) << 32 | x as u64;
+                    (splitmix64(self.glyph_salt.get() ^ cell) % variants.len() as u64) as usize
+                }
+                GlyphVariety::Fixed => 0,
+            }
+        };
+        variants[variant_index]
+    }
+
+    /// The glyph drawn for a heat value when glyphs follow the heat, as with
+    /// `glyph_stability`, e.g. for a legend of the ramp.
+    pub fn char_for_heat(&self, heat: u8) -> char {
+        let variants = &self.char_map[band(heat, self.char_map.len())];
+        variants[heat as usize % variants.len()]
+    }
+
+    /// The color drawn for a heat value, reduced to the color mode, e.g. for
+    /// a legend or a widget of your own. Effects that depend on where a cell
+    /// is, such as dithering, row palettes and age tint, are left out.
+    ///
+    /// ```
+    /// use fire_in_the_term::App;
+    /// use ratatui::style::Color;
+    ///
+    /// let app = App::new(40, 20);
+    /// assert_eq!(app.color_for_heat(0), Color::Black);
+    /// assert_eq!(app.color_for_heat(128), Color::Rgb(255, 200, 100));
+    /// assert_eq!(app.color_for_heat(255), Color::Rgb(255, 255, 250));
+    /// assert_eq!(app.char_for_heat(0), ' ');
+    /// assert_eq!(app.char_for_heat(128), '(');
+    /// assert_eq!(app.char_for_heat(255), '&');
+    /// ```
+    pub fn color_for_heat(&self, heat: u8) -> Color {
+        let bands = self.config.palette.len();
+        if self.config.transparent && band(heat, bands) == 0 {
+            return Color::Reset;
+        }
+        self.color_mode.reduce(self.full_color(heat))
+    }
+
+    /// The palette's color for a heat value, cross-fading from any old
+    /// palette, before it is reduced to the color mode.
+    fn full_color(&self, heat: u8) -> Color {
+        let bands = self.config.palette.len();
+        let mut color = self.config.palette[self.orient_band(band(heat, bands), bands)];
+        if let Some(old_palette) = &self.old_palette {
+            let old_band = self.orient_band(band(heat, old_palette.len()), old_palette.len());
+            color = palette::blend(old_palette[old_band], color, self.transition_progress);
+        }
+        color
+    }
+
+    /// A single color standing for the whole flame, e.g. to light a room in
+    /// time with it: the average of the colors of every cell warmer than the
+    /// coldest band, each weighted by its heat, so the hottest cells count
+    /// the most. A fire that is out gives the coldest color. The colors are
+    /// the palette's own, whatever the color mode.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, HEAT_SCALE, TICK_RATE, palette};
+    /// use ratatui::style::Color;
+    ///
+    /// // The left half of the grid white hot, the right half half as hot
+    /// let mut app = App::new(40, 20).with_post_process(Box::new(|grid| {
+    ///     for row in grid.iter_mut() {
+    ///         let (left, right) = row.split_at_mut(20);
+    ///         left.fill(255 * HEAT_SCALE);
+    ///         right.fill(128 * HEAT_SCALE);
+    ///     }
+    /// }));
+    /// app.update_fire(TICK_RATE);
+    ///
+    /// let (hot, warm) = (palette::to_rgb(app.color_for_heat(255)), palette::to_rgb(app.color_for_heat(128)));
+    /// let mix = |hot: u8, warm: u8| ((hot as f32 * 255.0 + warm as f32 * 128.0) / 383.0).round() as u8;
+    /// assert_eq!(
+    ///     app.dominant_color(),
+    ///     Color::Rgb(mix(hot.0, warm.0), mix(hot.1, warm.1), mix(hot.2, warm.2))
+    /// );
+    /// ```
+    pub fn dominant_color(&self) -> Color {
+        let bands = self.config.palette.len();
+        let (mut total, mut sums) = (0.0, [0.0; 3]);
+        for heat in self
+            .shown_grid()
+            .iter()
+            .flatten()
+            .map(|&heat| to_display(heat))
+        {
+            if band(heat, bands) == 0 {
+                continue;
+            }
+            let (r, g, b) = palette::to_rgb(self.full_color(heat));
+            let weight = heat as f32;
+            for (sum, channel) in sums.iter_mut().zip([r, g, b]) {
+                *sum += channel as f32 * weight;
+            }
+            total += weight;
+        }
+        if total == 0.0 {
+            return self.full_color(0);
+        }
+        let [r, g, b] = sums.map(|sum| (sum / total).round() as u8);
+        Color::Rgb(r, g, b)
+    }
+
+    /// Picks the palette entry for a heat value at a frame position.
+    fn color_index(&self, heat: u8, x: usize, y: usize) -> usize {
+        self.palette_index(heat, self.config.palette.len(), x, y)
+    }
+
+    /// Picks the entry for a heat value at a frame position in a palette of
+    /// `color_bands` colors.
+    fn palette_index(&self, heat: u8, color_bands: usize, x: usize, y: usize) -> usize {
+        let color_index = if self.config.dither {
+            dithered_band(heat, color_bands, x, y)
+        } else {
+            band(heat, color_bands)
+        };
+        self.orient_band(color_index, color_bands)
+    }
+
+    /// Turns a band around when the palette is drawn inverted.
+    fn orient_band(&self, index: usize, bands: usize) -> usize {
+        if self.config.invert {
+            bands - 1 - index
+        } else {
+            index
+        }
+    }
+
+    /// Resolves the palette color for a heat value at a frame position.
+    fn color(&self, heat: u8, x: usize, y: usize) -> Color {
+        if self.config.transparent && band(heat, self.config.palette.len()) == 0 {
+            return Color::Reset;
+        }
+        if self.gray_view {
+            return Color::Rgb(heat, heat, heat);
+        }
+        let row_palette = self.row_palette(y);
+        let mut color = match row_palette {
+            Some(row_palette) => row_palette[self.palette_index(heat, row_palette.len(), x, y)],
+            None => self.config.palette[self.color_index(heat, x, y)],
+        };
+        if let (Some(old_palette), None) = (&self.old_palette, row_palette) {
+            let old_color = old_palette[self.palette_index(heat, old_palette.len(), x, y)];
+            color = palette::blend(old_color, color, self.transition_progress);
+        }
+        if self.config.gas_flame {
+            let blue = palette::GAS_BLUE[self.palette_index(heat, palette::GAS_BLUE.len(), x, y)];
+            color = palette::blend(color, blue, self.gas_weight(heat, y));
+        }
+        if self.config.age_tint {
+            // Fade from whiter to the normal color as the cell ages
+            let age = self.display_age(x, y);
+            if age < FRESH_AGE && heat > 0 {
+                let freshness = 1.0 - age as f32 / FRESH_AGE as f32;
+                color = palette::blend(color, Color::White, 0.5 * freshness);
+            }
+        }
+        let (brightness, contrast) = (self.config.brightness, self.config.contrast);
+        if brightness != 1.0 || contrast != 1.0 {
+            color = palette::adjust(color, brightness, contrast);
+        }
+        color
+    }
+
+    /// The palette overriding the config's for flame row `y`, if any. The row
+    /// is scaled back to the flame height the ranges were given for.
+    fn row_palette(&self, y: usize) -> Option<&[Color]> {
+        if self.row_palettes.is_empty() {
+            return None;
+        }
+        let row = y * self.row_palettes_height / self.flame_height().max(1);
+        self.row_palettes
+            .iter()
+            .find(|(rows, _)| rows.contains(&row))
+            .map(|(_, row_palette)| row_palette.as_slice())
+    }
+
+    /// The flame row drawn at frame row `y`, which is turned about the middle
+    /// row while flipping. `None` where the turned flame doesn't reach `y`.
+    fn flipped_row(&self, y: usize) -> Option<usize> {
+        let height = self.flame_height();
+        if self.flip_progress >= 1.0 {
+            return Some(if self.flipped { height - 1 - y } else { y });
+        }
+        let turned = if self.flipped {
+            self.flip_progress
+        } else {
+            1.0 - self.flip_progress
+        };
+        // Rows squeeze towards the middle as the flame turns edge-on
+        let middle = (height as f32 - 1.0) / 2.0;
+        let row = (middle + (y as f32 - middle) / (turned * PI).cos()).round();
+        (row >= 0.0 && row < height as f32).then_some(row as usize)
+    }
+
+    /// How blue a cell of a gas flame is, from 0 for the palette color to 1.
+    /// Only hot cells in the bottom `gas_transition` of the flame turn blue,
+    /// fading out over a few rows above it.
+    fn gas_weight(&self, heat: u8, y: usize) -> f32 {
+        let flame_height = self.flame_height();
+        let height_above_base = (flame_height - 1 - y.min(flame_height - 1)) as f32;
+        let transition_row = self.config.gas_transition * flame_height as f32;
+        let zone = ((transition_row - height_above_base) / GAS_FADE_ROWS + 0.5).clamp(0.0, 1.0);
+        let hot = ((heat as f32 - GAS_MIN_HEAT) / (255.0 - GAS_MIN_HEAT)).clamp(0.0, 1.0);
+        zone * hot.sqrt()
+    }
+
+    /// Renders the current frame as styled text, one line per frame row.
+    ///
+    /// Full heat lands on the last glyph and color band of any ramp and
+    /// palette:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, HEAT_SCALE, config::FireConfig, palette, ramp::CharRamp};
+    /// use ratatui::style::Color;
+    ///
+    /// for char_ramp in CharRamp::ALL.into_iter().chain([CharRamp::Safe]) {
+    ///     for bands in 2..=24 {
+    ///         for dither in [false, true] {
+    ///             let config = FireConfig {
+    ///                 palette: palette::gradient(Color::Black, Color::White, bands),
+    ///                 char_ramp,
+    ///                 dither,
+    ///                 ..FireConfig::default()
+    ///             };
+    ///             let mut app = App::new(20, 10).with_config(config);
+    ///             app.set_grid(&vec![vec![255 * HEAT_SCALE; 20]; 10]);
+    ///             assert_eq!(app.render_fire().lines.len(), 10);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn render_fire(&self) -> Text<'_> {
+        let mut upright = self.render_upright();
+        // The fires behind show through wherever the ones in front are blank.
+        // Close-ups and the inspection views only show the front one.
+        if self.zoom == 1 && !self.show_heat && !self.gray_view {
+            for layer in &self.behind {
+                let behind = layer.render_upright();
+                for (line, behind_line) in upright.lines.iter_mut().zip(behind.lines) {
+                    for (span, behind_span) in line.spans.iter_mut().zip(behind_line.spans) {
+                        if span.content == " " {
+                            *span = behind_span;
+                        }
+                    }
+                }
+            }
+        }
+        if !self.config.rotate {
+            return upright;
+        }
+        // Turn the frame so the base runs down the left edge, its bottom row
+        // becoming the leftmost column
+        let lines = (0..self.display_width).map(|column| {
+            let spans = (upright.lines.iter().rev())
+                .filter_map(|line| line.spans.get(column).cloned())
+                .collect::<Vec<_>>();
+            Line::from(spans)
+        });
+        Text::from(lines.collect::<Vec<_>>())
+    }
+
+    /// Renders the frame the right way up, before any rotation.
+    fn render_upright(&self) -> Text<'_> {
+        if self.config.glyph_variety == GlyphVariety::Hashed {
+            self.glyph_salt.set(self.glyph_rng.borrow_mut().random());
+        }
+        let base_style = match self.config.background() {
+            Some(background) => Style::default().bg(self.color_mode.reduce(background)),
+            None => Style::default(),
+        };
+        let (flame_width, flame_height) = (self.flame_width(), self.flame_height());
+        let mut lines = Vec::with_capacity(self.display_height);
+
+        // Draws each cell of frame row `row` across `cell_width` columns and
+        // pads out the frame. Cells without a color use the terminal's
+        // foreground.
+        let cells_to_line = |row: usize, cells: Vec<(char, Option<Color>)>| {
+            let mut spans = Vec::with_capacity(self.display_width);
+            for (column, (glyph, color)) in cells.into_iter().enumerate() {
+                let style = match color {
+                    Some(mut color) => {
+                        let vignette = self.vignette.get(row).and_then(|row| row.get(column));
+                        if let (Some(&brightness), false) = (vignette, color == Color::Reset) {
+                            color = palette::adjust(color, brightness, 1.0);
+                        }
+                        base_style.fg(self.color_mode.reduce(color))
+                    }
+                    None => base_style,
+                };
+                let span = Span::styled(glyph.to_string(), style);
+                spans.extend(std::iter::repeat_n(span, self.cell_width()));
+            }
+            let padding = self.display_width - spans.len();
+            spans.extend(std::iter::repeat_n(Span::styled(" ", base_style), padding));
+            Line::from(spans)
+        };
+
+        if self.zoom > 1 {
+            // Draw each cell of the viewport as a zoom by zoom block, filling
+            // the whole frame in place of the reflection
+            let (zoom, viewport) = (self.zoom as usize, self.viewport);
+            for row in viewport.top() as usize..viewport.bottom() as usize {
+                let Some(y) = self.flipped_row(row) else {
+                    lines.extend(std::iter::repeat_n(cells_to_line(0, Vec::new()), zoom));
+                    continue;
+                };
+                let cells = (viewport.left() as usize..viewport.right() as usize)
+                    .flat_map(|x| {
+                        let heat = self.display_heat(x, y);
+                        let cell = match (self.speck(x, y), self.ash(heat, x, y)) {
+                            (Some((glyph, color)), _) => {
+                                (glyph, (!self.config.mono).then_some(color))
+                            }
+                            (None, Some(ash)) => (ASH_GLYPH, (!self.config.mono).then_some(ash)),
+                            (None, None) => {
+                                let color = (!self.config.mono).then(|| self.color(heat, x, y));
+                                (self.glyph(heat, x, y), color)
+                            }
+                        };
+                        std::iter::repeat_n(cell, zoom)
+                    })
+                    .take(flame_width)
+                    .collect();
+                lines.extend(std::iter::repeat_n(cells_to_line(lines.len(), cells), zoom));
+            }
+            lines.resize(self.display_height, cells_to_line(0, Vec::new()));
+            return Text::from(lines);
+        }
+
+        // Braille dots stand in for the glyphs, except when showing the heat
+        let braille = self.config.braille && !self.show_heat;
+        for row in 0..flame_height {
+            let Some(y) = self.flipped_row(row) else {
+                lines.push(cells_to_line(lines.len(), Vec::new()));
+                continue;
+            };
+            let cells = (0..flame_width)
+                .map(|x| {
+                    if let Some((glyph, color)) = self.speck(x, y) {
+                        return (glyph, (!self.config.mono).then_some(color));
+                    }
+                    let (glyph, heat) = if braille {
+                        self.braille_cell(x, y, 1.0)
+                    } else {
+                        let heat = self.display_heat(x, y);
+                        if let Some(ash) = self.ash(heat, x, y) {
+                            return (ASH_GLYPH, (!self.config.mono).then_some(ash));
+                        }
+                        (self.glyph(heat, x, y), heat)
+                    };
+                    let color = (!self.config.mono).then(|| self.color(heat, x, y));
+                    (glyph, color)
+                })
+                .collect();
+            lines.push(cells_to_line(lines.len(), cells));
+        }
+
+        // Mirror the lowest flame rows below the flame, dimming them with
+        // depth and shifting each row sideways a little so the water ripples
+        let reflection_height = self.display_height - flame_height;
+        for row in 0..reflection_height.min(flame_height) {
+            let Some(source_y) = self.flipped_row(flame_height - 1 - row) else {
+                lines.push(cells_to_line(lines.len(), Vec::new()));
+                continue;
+            };
+            let depth = row as f32 / reflection_height as f32;
+            let ripple = (self.ticks as f32 * 0.3 + row as f32 * 0.9).sin() * 1.5;
+            let cells = (0..flame_width)
+                .map(|x| {
+                    let source_x = (x as isize + ripple.round() as isize)
+                        .clamp(0, flame_width as isize - 1)
+                        as usize;
+                    let dim = 0.6 - 0.3 * depth;
+                    let (glyph, dimmed_heat) = if braille {
+                        self.braille_cell(source_x, source_y, dim)
+                    } else {
+                        let dimmed_heat =
+                            (self.display_heat(source_x, source_y) as f32 * dim) as u8;
+                        (self.glyph(dimmed_heat, source_x, source_y), dimmed_heat)
+                    };
+                    let color = (!self.config.mono).then(|| {
+                        palette::blend(
+                            self.color(dimmed_heat, source_x, source_y),
+                            REFLECTION_TINT,
+                            0.4,
+                        )
+                    });
+                    (glyph, color)
+                })
+                .collect();
+            lines.push(cells_to_line(lines.len(), cells));
+        }
+        Text::from(lines)
+    }
+
+    /// Renders the flame as a sixel image in the palette colors, drawing each
+    /// cell as a `cell_width` by `cell_height` block of pixels.
+    pub fn to_sixel(&self, cell_width: usize, cell_height: usize) -> String {
+        let grid: Vec<Vec<usize>> = (0..self.flame_height())
+            .map(|y| {
+                (0..self.flame_width())
+                    .map(|x| self.color_index(self.display_heat(x, y), x, y))
+                    .collect()
+            })
+            .collect();
+        sixel::grid_to_sixel(
+            &grid,
+            &self.config.palette,
+            cell_width * self.cell_width(),
+            cell_height,
+        )
+    }
+
+    /// Renders the current frame as plain text, one line per grid row.
+    pub fn render_plain(&self) -> String {
+        let text = self.render_fire();
+        let mut output = String::with_capacity((self.display_width + 1) * self.display_height);
+        for line in &text.lines {
+            let row: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            output.push_str(row.trim_end());
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Renders the current frame as ANSI-colored text. Only SGR color codes are
+    /// used, with no cursor movement, so the output can be pasted into docs or
+    /// converted to HTML.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE};
+    ///
+    /// let mut app = App::new(20, 10).with_seed(1);
+    /// for _ in 0..50 {
+    ///     app.update_fire(TICK_RATE);
+    /// }
+    /// let text = app.to_ansi_string();
+    /// assert_eq!(text.lines().count(), 10);
+    /// // Every line sets its colors and resets them at the end
+    /// assert!(text.lines().all(|line| line.ends_with("\x1b[0m")));
+    /// assert!(text.contains("\x1b[38;"));
+    /// // Only SGR codes are used, each ended by `m`
+    /// assert!(text.split("\x1b[").skip(1).all(|code| {
+    ///     let end = code.find(|c: char| !c.is_ascii_digit() && c != ';');
+    ///     end.is_some_and(|end| code[end..].starts_with('m'))
+    /// }));
+    /// ```
+    pub fn to_ansi_string(&self) -> String {
+        let mut output = String::new();
+        for line in &self.render_fire().lines {
+            push_ansi_line(&mut output, line);
+            output.push('\n');
+        }
+        output
+    }
+
+    /// Renders row `y` of the frame, counted from the top, like
+    /// [`App::to_ansi_string`] but without the trailing newline, e.g. for a
+    /// status bar.
+    pub fn row_to_ansi_string(&self, y: usize) -> String {
+        let mut output = String::new();
+        if let Some(line) = self.render_fire().lines.get(y) {
+            push_ansi_line(&mut output, line);
+        }
+        output
+    }
+}
+
+/// Writes a rendered line with SGR codes for its colors, resetting them at
+/// the end.
+fn push_ansi_line(output: &mut String, line: &Line) {
+    let (mut current_fg, mut current_bg) = (None, None);
+    for span in &line.spans {
+        if span.style.fg != current_fg {
+            if let Some(color) = span.style.fg {
+                output.push_str(&format!("\x1b[{}m", palette::sgr_foreground(color)));
+            }
+            current_fg = span.style.fg;
+        }
+        if span.style.bg != current_bg {
+            if let Some(color) = span.style.bg {
+                output.push_str(&format!("\x1b[{}m", palette::sgr_background(color)));
+            }
+            current_bg = span.style.bg;
+        }
+        output.push_str(&span.content);
+    }
+    output.push_str("\x1b[0m");
+}
+
+/// Scales a grid heat down to the 0-255 range that drawing uses.
+// Without `precise-heat` this is already a `u8`
+#[allow(clippy::unnecessary_cast)]
+fn to_display(heat: Heat) -> u8 {
+    (heat / HEAT_SCALE) as u8
+}
+
+/// Copies `old` into `new` resting on the bottom row and centered across,
+/// cutting off whatever doesn't fit.
+fn anchor_bottom<T: Copy>(old: &[Vec<T>], new: &mut [Vec<T>]) {
+    for (old_row, new_row) in old.iter().rev().zip(new.iter_mut().rev()) {
+        let (old_width, width) = (old_row.len(), new_row.len());
+        if width >= old_width {
+            let left = (width - old_width) / 2;
+            new_row[left..left + old_width].copy_from_slice(old_row);
+        } else {
+            let left = (old_width - width) / 2;
+            new_row.copy_from_slice(&old_row[left..left + width]);
+        }
+    }
+}
+
+/// Works out the frame after `grid` under `config`, without an [`App`] to
+/// keep around, e.g. to test the physics on its own. Any randomness is drawn
+/// from `rng`. Each call starts from still air, so gusts, flare-ups and other
+/// motion built up over several frames don't carry over the way they do in a
+/// running [`App`].
+///
+/// Panics if the rows differ in length.
+///
+/// ```
+/// use fire_in_the_term::{config::FireConfig, step_grid};
+/// use rand::{SeedableRng, rngs::StdRng};
+///
+/// let mut rng = StdRng::seed_from_u64(1);
+/// let cold = vec![vec![0; 40]; 20];
+/// let next = step_grid(&cold, &FireConfig::default(), &mut rng);
+/// // The bottom row catches, far hotter than the faint sparks above it
+/// assert!(next[19].iter().max() > next[..19].iter().flatten().max());
+/// ```
+///
+/// Heat only comes from the source. With nothing rising, kept or spread
+/// from the sides, a cold grid lights its bottom row and the cells above
+/// get no more than the flicker every cell gets:
+///
+/// ```
+/// use fire_in_the_term::{HEAT_SCALE, config::FireConfig, step_grid};
+/// use rand::{SeedableRng, rngs::StdRng};
+///
+/// let config = FireConfig {
+///     rise_strength: 0.0,
+///     self_weight: 0.0,
+///     side_diffusion: 0.0,
+///     ..FireConfig::default()
+/// };
+/// for seed in 0..20 {
+///     let mut rng = StdRng::seed_from_u64(seed);
+///     let next = step_grid(&vec![vec![0; 40]; 30], &config, &mut rng);
+///     assert!(next[..29].iter().flatten().all(|&heat| heat <= 15 * HEAT_SCALE));
+///     assert!(next[29].iter().any(|&heat| heat >= 200 * HEAT_SCALE));
+/// }
+/// ```
+pub fn step_grid(grid: &[Vec<Heat>], config: &FireConfig, rng: &mut impl Rng) -> Vec<Vec<Heat>> {
+    let (width, height) = (grid.first().map_or(0, Vec::len), grid.len());
+    assert!(
+        grid.iter().all(|row| row.len() == width),
+        "the rows of the grid differ in length"
+    );
+    if width == 0 {
+        return grid.to_vec();
+    }
+    // A fixed simulation size keeps the grid as given whatever else the
+    // config asks for
+    let config = FireConfig {
+        sim_size: Some((width, height)),
+        ..config.clone()
+    };
+    // Seeded from `rng`, so noise and per-cell rolls keyed on the seed are
+    // as repeatable as the rest
+    let mut app = App::new(width, height)
+        .with_config(config)
+        .with_seed(rng.random());
+    app.fire_grid = grid.to_vec();
+    app.step(1.0);
+    app.fire_grid
+}
+
+/// The glyph variants for each heat band under `config`.
+fn char_map(config: &FireConfig) -> Vec<Vec<char>> {
+    if config.align_bands {
+        config.char_ramp.char_map_with_bands(config.palette.len())
+    } else {
+        config.char_ramp.char_map()
+    }
+}
+
+/// Maps a heat value onto one of `bands` evenly sized bands. Clamped in case
+/// float rounding ever lands past the last band.
+fn band(heat: u8, bands: usize) -> usize {
+    let last_band = bands - 1;
+    ((heat as f32 / 255.0 * last_band as f32) as usize).min(last_band)
+}
+
+/// Ordered dithering thresholds, tiled across the frame. Neighboring cells
+/// get thresholds far apart, so a heat between two bands turns into an even
+/// mix of both.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Like [`band`], but a heat between two bands picks the upper one at some
+/// frame positions and the lower one at others, in proportion to how close it
+/// is to each.
+fn dithered_band(heat: u8, bands: usize, x: usize, y: usize) -> usize {
+    let last_band = bands - 1;
+    let position = heat as f32 / 255.0 * last_band as f32;
+    let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0;
+    let lower = position as usize;
+    (lower + usize::from(position.fract() > threshold)).min(last_band)
+}
+
+/// Brightness to draw each cell of a `width` by `height` frame at, by row and
+/// column: full in the middle, easing down by `strength` at the corners.
+/// Empty when `strength` is 0.
+fn vignette(strength: f32, width: usize, height: usize) -> Vec<Vec<f32>> {
+    if strength == 0.0 {
+        return Vec::new();
+    }
+    let offset = |i: usize, size: usize| (i as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    // Squared distance from the middle, 1 at the corners
+                    let distance = (offset(x, width).powi(2) + offset(y, height).powi(2)) / 2.0;
+                    1.0 - strength * distance
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Which cells of a `width` by `height` grid can hold heat under `mask`,
+/// stretching its rows of text over the grid. Empty when `mask` is.
+fn burnable(mask: &[String], width: usize, height: usize) -> Vec<Vec<bool>> {
+    if mask.is_empty() {
+        return Vec::new();
+    }
+    let rows: Vec<Vec<char>> = mask.iter().map(|row| row.chars().collect()).collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    (0..height)
+        .map(|y| {
+            let row = &rows[y * rows.len() / height];
+            (0..width)
+                .map(|x| row.get(x * columns / width).is_some_and(|&c| c != ' '))
+                .collect()
+        })
+        .collect()
+}
+
+/// How much of each column's heat to draw across a frame `width` columns
+/// wide, easing from nearly nothing at the sides to all of it `falloff`
+/// columns in. Empty when `falloff` is 0.
+fn edge_weights(falloff: usize, width: usize) -> Vec<f32> {
+    if falloff == 0 {
+        return Vec::new();
+    }
+    (0..width)
+        .map(|x| {
+            let distance = x.min(width - 1 - x) as f32 + 0.5;
+            let t = (distance / falloff as f32).min(1.0);
+            t * t * (3.0 - 2.0 * t)
+        })
+        .collect()
+}
+
+/// Share of a side neighbor's heat that diffuses into a cell, for each
+/// distance from 1 to `radius`. Closer neighbors give more, and each side
+/// gives `side` in total however wide the radius.
+fn side_weights(radius: usize, side: f32) -> impl Iterator<Item = f32> {
+    let total = (radius * (radius + 1) / 2) as f32;
+    (1..=radius).map(move |distance| (radius + 1 - distance) as f32 / total * side)
+}
+
+/// The random numbers each cell of a row takes as it rises: how much heat it
+/// decays by and how far it flickers, and which way.
+#[derive(Default)]
+struct RowRolls {
+    decay: Vec<Heat>,
+    fluctuation: Vec<Heat>,
+    up: Vec<bool>,
+}
+
+impl RowRolls {
+    /// Empties the rolls, keeping room for a row `width` cells wide.
+    fn reserve(&mut self, width: usize) {
+        self.clear();
+        self.decay.reserve(width);
+        self.fluctuation.reserve(width);
+        self.up.reserve(width);
+    }
+
+    fn clear(&mut self) {
+        self.decay.clear();
+        self.fluctuation.clear();
+        self.up.clear();
+    }
+
+    /// Draws the next cell's numbers from `rng`, always in the same order.
+    fn draw(&mut self, rng: &mut dyn RngCore, tip_factor: f32, decay_scale: f32) {
+        let decay_roll = rng.random_range(15..=18);
+        let fluctuation = rng.random_range(12 * HEAT_SCALE..=15 * HEAT_SCALE);
+        let fluctuates_up = rng.random_bool(0.5);
+        self.decay
+            .push((decay_roll as f32 * tip_factor * decay_scale * HEAT_SCALE as f32) as Heat);
+        self.fluctuation.push(fluctuation);
+        self.up.push(fluctuates_up);
+    }
+}
+
+/// Adds up the heat each cell of `row` takes from its side neighbors into
+/// `left` and `right`, one distance at a time across the whole row.
+fn side_sums(row: &[Heat], weights: &[f32], left: &mut [f32], right: &mut [f32]) {
+    left.fill(0.0);
+    right.fill(0.0);
+    for (distance, &weight) in (1..row.len()).zip(weights) {
+        for (sum, &heat) in left[distance..].iter_mut().zip(row) {
+            *sum += heat as f32 * weight;
+        }
+        for (sum, &heat) in right.iter_mut().zip(&row[distance..]) {
+            *sum += heat as f32 * weight;
+        }
+    }
+}
+
+/// Works out a row of the rise into `next` from the row's last heat, the
+/// row below it, its rolls and its side sums. It does the same sums as the
+/// cell by cell loop in `App::step`, without the wind, bursts, draft or
+/// cooling noise, and with no branches or bounds checks in the way of
+/// vectorizing.
+fn rise_row(
+    next: &mut [Heat],
+    row: &[Heat],
+    below: &[Heat],
+    rolls: &RowRolls,
+    (left, right): (&[f32], &[f32]),
+    rise_strength: f32,
+    self_weight: f32,
+) {
+    let heat = row.iter().zip(below).zip(left.iter().zip(right));
+    let rolls = rolls.decay.iter().zip(&rolls.fluctuation).zip(&rolls.up);
+    for (next, (((&current, &below), (&left, &right)), ((&decay, &fluctuation), &up))) in
+        next.iter_mut().zip(heat.zip(rolls))
+    {
+        let rising_heat = (below as f32 * rise_strength) as Heat;
+        let own_heat = (current as f32 * self_weight) as Heat;
+        let decayed_heat = rising_heat
+            .saturating_add(own_heat)
+            .saturating_add(left as Heat)
+            .saturating_add(right as Heat)
+            .saturating_sub(decay + ROUNDING_LOSS);
+        *next = if up {
+            decayed_heat.saturating_add(fluctuation)
+        } else {
+            decayed_heat.saturating_sub(fluctuation)
+        };
+    }
+}
+
+/// Smooth noise from -1 to 1 through space and time, for
+/// `coherent_flicker`. Every whole point gets a random value from the seed,
+/// and the points between blend smoothly from their eight neighbours, so
+/// close points and moments get close values.
+fn flicker_noise(seed: u64, x: f32, y: f32, time: f32) -> f32 {
+    let corner = |x: i64, y: i64, time: i64| {
+        let hash = [x, y, time]
+            .into_iter()
+            .fold(splitmix64(seed), |state, value| {
+                splitmix64(state ^ value as u64)
+            });
+        (hash >> 40) as f32 / (1 << 23) as f32 - 1.0
+    };
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let lerp = |from: f32, to: f32, t: f32| from + (to - from) * t;
+    let (x0, y0, time0) = (x.floor(), y.floor(), time.floor());
+    let (tx, ty, tt) = (smooth(x - x0), smooth(y - y0), smooth(time - time0));
+    let (x0, y0, time0) = (x0 as i64, y0 as i64, time0 as i64);
+    let slice = |time| {
+        let top = lerp(corner(x0, y0, time), corner(x0 + 1, y0, time), tx);
+        let bottom = lerp(corner(x0, y0 + 1, time), corner(x0 + 1, y0 + 1, time), tx);
+        lerp(top, bottom, ty)
+    };
+    lerp(slice(time0), slice(time0 + 1), tt)
+}
+
+/// Spacing in cells between the random points of the cooling noise.
+const NOISE_CELL: usize = 4;
+
+/// Builds a smooth value-noise field from 0 to 1 covering at least `height`
+/// rows, which wraps around vertically so it can scroll forever.
+fn value_noise(rng: &mut ChaCha12Rng, width: usize, height: usize) -> Vec<Vec<f32>> {
+    let lattice_rows = height.div_ceil(NOISE_CELL);
+    let lattice_columns = width.div_ceil(NOISE_CELL) + 1;
+    let lattice: Vec<Vec<f32>> = (0..lattice_rows)
+        .map(|_| (0..lattice_columns).map(|_| rng.random()).collect())
+        .collect();
+
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    (0..lattice_rows * NOISE_CELL)
+        .map(|y| {
+            let (row, ty) = (
+                y / NOISE_CELL,
+                smooth((y % NOISE_CELL) as f32 / NOISE_CELL as f32),
+            );
+            let (top, bottom) = (&lattice[row], &lattice[(row + 1) % lattice_rows]);
+            (0..width)
+                .map(|x| {
+                    let column = x / NOISE_CELL;
+                    let tx = smooth((x % NOISE_CELL) as f32 / NOISE_CELL as f32);
+                    let upper = top[column] + (top[column + 1] - top[column]) * tx;
+                    let lower = bottom[column] + (bottom[column + 1] - bottom[column]) * tx;
+                    upper + (lower - upper) * ty
+                })
+                .collect()
+        })
+        .collect()
+}
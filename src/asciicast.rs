@@ -0,0 +1,42 @@
+use crate::events::json_string;
+use fire_in_the_term::{App, TICK_RATE};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Simulates `frames` frames of `app` and writes them to `path` as an
+/// asciinema v2 recording of a `width` by `height` terminal, one output
+/// event per frame, `TICK_RATE` apart.
+pub fn export(
+    app: &mut App,
+    path: &Path,
+    frames: usize,
+    width: u16,
+    height: u16,
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(
+        out,
+        "{{\"version\":2,\"width\":{width},\"height\":{height},\"env\":{{\"TERM\":\"xterm-256color\"}}}}"
+    )?;
+    for frame in 0..frames {
+        // Hide the cursor and clear the screen first, then draw every frame
+        // over the last from the top left
+        let mut output = String::from(if frame == 0 {
+            "\x1b[?25l\x1b[2J\x1b[H"
+        } else {
+            "\x1b[H"
+        });
+        output.push_str(
+            &app.to_ansi_string()
+                .trim_end_matches('\n')
+                .replace('\n', "\r\n"),
+        );
+        let time = frame as f64 * TICK_RATE.as_secs_f64();
+        writeln!(out, "[{time:.6}, \"o\", {}]", json_string(&output))?;
+        app.update_fire(TICK_RATE);
+    }
+    out.flush()
+}
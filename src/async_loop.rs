@@ -0,0 +1,102 @@
+use crate::{
+    SIGNAL_CHECK_INTERVAL, Session, advance_apps, draw_frame, end_frame, handle_event, hung_up,
+};
+use crossterm::event::Event;
+use fire_in_the_term::App;
+use futures::{Stream, StreamExt};
+use ratatui::{Terminal, backend::Backend};
+use std::io;
+use tokio::time::{self, Instant};
+
+/// Runs the animation until the user quits, like the polling loop, but waits
+/// for terminal events and ticks asynchronously so other tasks on the same
+/// runtime keep running. The simulation itself stays synchronous. Events
+/// are read from `events`, e.g. crossterm's `EventStream`.
+pub async fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    apps: &mut [App],
+    session: &mut Session,
+    mut events: impl Stream<Item = io::Result<Event>> + Unpin,
+) -> io::Result<()> {
+    let mut next_tick = Instant::now();
+    let mut last_tick = Instant::now();
+    let mut redraw = true;
+
+    loop {
+        let frame_start = Instant::now();
+        if redraw && session.should_draw() {
+            draw_frame(terminal, apps, session)?;
+        }
+        let draw_cost = frame_start.elapsed();
+        redraw = true;
+        let toasting = apps.iter().any(|app| app.toast().is_some());
+
+        tokio::select! {
+            event = events.next() => match event {
+                Some(event) => {
+                    handle_event(event.map_err(hung_up)?, apps, session);
+                    if session.should_quit() {
+                        return Ok(());
+                    }
+                }
+                // The terminal has gone away
+                None => return Ok(()),
+            },
+            // While paused only events wake the loop
+            _ = time::sleep_until(next_tick), if !session.paused() => {
+                let update_start = Instant::now();
+                let dt = update_start - last_tick;
+                advance_apps(apps, session, dt);
+                end_frame(apps, session, dt, draw_cost + update_start.elapsed());
+                last_tick = update_start;
+                next_tick = Instant::now() + session.next_interval();
+            }
+            // Paused loops still wake up now and then to check for signals,
+            // and to take down a toast once it has timed out
+            _ = time::sleep(SIGNAL_CHECK_INTERVAL), if session.paused() => redraw = toasting,
+        }
+        if session.should_quit() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::end_on_disconnect;
+    use futures::stream;
+    use ratatui::backend::TestBackend;
+
+    fn run(events: Vec<io::Result<Event>>) -> io::Result<()> {
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        let mut apps = vec![App::new(40, 10).with_seed(1)];
+        let mut session = Session::new(false, false, None);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        end_on_disconnect(runtime.block_on(run_app(
+            &mut terminal,
+            &mut apps,
+            &mut session,
+            stream::iter(events),
+        )))
+    }
+
+    #[test]
+    fn run_app_returns_when_the_events_fail() {
+        let fail = |err| run(vec![Err(err)]);
+        assert!(fail(io::Error::from(io::ErrorKind::BrokenPipe)).is_ok());
+        assert!(fail(io::Error::from(io::ErrorKind::UnexpectedEof)).is_ok());
+        assert!(fail(io::Error::from(io::ErrorKind::ConnectionReset)).is_ok());
+        #[cfg(unix)]
+        assert!(fail(io::Error::from_raw_os_error(5)).is_ok());
+        assert!(fail(io::Error::other("unreadable")).is_err());
+    }
+
+    #[test]
+    fn run_app_returns_when_the_events_run_out() {
+        assert!(run(Vec::new()).is_ok());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    App, PostProcess, config::FireConfig, palette::ColorMode, ramp::CharRamp, shape::FlameShape,
+};
+use ratatui::{layout::Alignment, style::Color};
+use std::ops::Range;
+
+/// Frame size an [`AppBuilder`] starts from, the classic terminal size.
+const DEFAULT_SIZE: (usize, usize) = (80, 24);
+
+/// Sets up an [`App`] one setting at a time, starting from the default config
+/// on an 80x24 frame. Anything not covered by a setter can be changed through
+/// [`AppBuilder::config`].
+///
+/// ```
+/// use fire_in_the_term::{App, TICK_RATE, palette, ramp::CharRamp};
+///
+/// let mut app = App::builder()
+///     .size(100, 30)
+///     .palette(palette::ICE.to_vec())
+///     .char_ramp(CharRamp::Blocks)
+///     .seed(7)
+///     .build();
+/// app.update_fire(TICK_RATE);
+///
+/// assert_eq!(app.display_size(), (100, 30));
+/// assert_eq!(app.seed(), 7);
+/// assert_eq!(app.config.char_ramp, CharRamp::Blocks);
+/// ```
+pub struct AppBuilder {
+    size: (usize, usize),
+    config: FireConfig,
+    seed: Option<u64>,
+    glyph_seed: Option<u64>,
+    history: usize,
+    history_memory: Option<usize>,
+    color_mode: ColorMode,
+    alignment: Alignment,
+    opacity: f32,
+    post_process: Option<PostProcess>,
+    row_palettes: Vec<(Range<usize>, Vec<Color>)>,
+}
+
+impl AppBuilder {
+    pub fn new() -> AppBuilder {
+        AppBuilder {
+            size: DEFAULT_SIZE,
+            config: FireConfig::default(),
+            seed: None,
+            glyph_seed: None,
+            history: 0,
+            history_memory: None,
+            color_mode: ColorMode::default(),
+            alignment: Alignment::Center,
+            opacity: 1.0,
+            post_process: None,
+            row_palettes: Vec::new(),
+        }
+    }
+
+    /// Size of the rendered frame. Drawing through [`crate::FireWidget`]
+    /// resizes it to the area anyway.
+    pub fn size(mut self, width: usize, height: usize) -> AppBuilder {
+        self.size = (width, height);
+        self
+    }
+
+    /// Replaces the whole config, including anything set before.
+    pub fn config(mut self, config: FireConfig) -> AppBuilder {
+        self.config = config;
+        self
+    }
+
+    /// Flame colors from the coldest heat band to the hottest.
+    pub fn palette(mut self, palette: Vec<Color>) -> AppBuilder {
+        self.config.palette = palette;
+        self
+    }
+
+    /// See [`App::set_intensity`].
+    pub fn intensity(mut self, intensity: f32) -> AppBuilder {
+        self.config.intensity = intensity.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn char_ramp(mut self, char_ramp: CharRamp) -> AppBuilder {
+        self.config.char_ramp = char_ramp;
+        self
+    }
+
+    pub fn shape(mut self, shape: FlameShape) -> AppBuilder {
+        self.config.shape = Some(shape);
+        self
+    }
+
+    /// See [`App::with_seed`].
+    pub fn seed(mut self, seed: u64) -> AppBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// See [`App::with_glyph_seed`].
+    pub fn glyph_seed(mut self, seed: u64) -> AppBuilder {
+        self.glyph_seed = Some(seed);
+        self
+    }
+
+    /// See [`App::with_history`].
+    pub fn history(mut self, capacity: usize) -> AppBuilder {
+        self.history = capacity;
+        self
+    }
+
+    /// See [`App::with_history_memory`].
+    pub fn history_memory(mut self, bytes: usize) -> AppBuilder {
+        self.history_memory = Some(bytes);
+        self
+    }
+
+    /// See [`App::with_color_mode`].
+    pub fn color_mode(mut self, color_mode: ColorMode) -> AppBuilder {
+        self.color_mode = color_mode;
+        self
+    }
+
+    /// See [`App::with_alignment`].
+    pub fn alignment(mut self, alignment: Alignment) -> AppBuilder {
+        self.alignment = alignment;
+        self
+    }
+
+    /// See [`App::with_opacity`].
+    pub fn opacity(mut self, opacity: f32) -> AppBuilder {
+        self.opacity = opacity;
+        self
+    }
+
+    /// See [`App::with_post_process`].
+    pub fn post_process(mut self, post_process: PostProcess) -> AppBuilder {
+        self.post_process = Some(post_process);
+        self
+    }
+
+    /// See [`App::with_row_palettes`]. The rows count from the top of the
+    /// flame at the builder's size.
+    pub fn row_palettes(mut self, row_palettes: Vec<(Range<usize>, Vec<Color>)>) -> AppBuilder {
+        self.row_palettes = row_palettes;
+        self
+    }
+
+    pub fn build(self) -> App {
+        let (width, height) = self.size;
+        let mut app = App::new(width, height)
+            .with_config(self.config)
+            .with_history(self.history)
+            .with_color_mode(self.color_mode)
+            .with_alignment(self.alignment)
+            .with_opacity(self.opacity);
+        if let Some(seed) = self.seed {
+            app = app.with_seed(seed);
+        }
+        if let Some(seed) = self.glyph_seed {
+            app = app.with_glyph_seed(seed);
+        }
+        if let Some(bytes) = self.history_memory {
+            app = app.with_history_memory(bytes);
+        }
+        if let Some(post_process) = self.post_process {
+            app = app.with_post_process(post_process);
+        }
+        if !self.row_palettes.is_empty() {
+            app = app.with_row_palettes(self.row_palettes);
+        }
+        app
+    }
+}
+
+impl Default for AppBuilder {
+    fn default() -> Self {
+        AppBuilder::new()
+    }
+}
+
+impl Default for App {
+    /// An 80x24 fire with the default config.
+    fn default() -> Self {
+        AppBuilder::new().build()
+    }
+}
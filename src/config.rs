@@ -0,0 +1,973 @@
+use crate::{
+    effect::Effect,
+    keys::{Action, Key},
+    palette::{self, PaletteError, Preset},
+    ramp::{CharRamp, GlyphVariety},
+    shape::FlameShape,
+};
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+/// Tunable parameters of the fire simulation and its rendering. Every field
+/// can be set from a TOML config file; missing fields keep their defaults.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FireConfig {
+    /// Flame colors from the coldest heat band to the hottest.
+    /// [`App::set_palette`](crate::App::set_palette) cross-fades to a new one.
+    pub palette: Vec<Color>,
+    /// Frames taken to cross-fade to a new palette. 0 switches instantly.
+    pub palette_fade_frames: u32,
+    /// Flip the palette so hot cells are dark and cold cells are bright, for a
+    /// shadow flame on a light background.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, config::FireConfig};
+    ///
+    /// let config = FireConfig { invert: true, ..FireConfig::default() };
+    /// let [plain, inverted] =
+    ///     [FireConfig::default(), config].map(|config| App::new(40, 20).with_config(config));
+    /// // The coldest and hottest bands trade colors
+    /// assert_eq!(inverted.color_for_heat(0), plain.color_for_heat(255));
+    /// assert_eq!(inverted.color_for_heat(255), plain.color_for_heat(0));
+    /// ```
+    pub invert: bool,
+    /// Background color behind the flame. When inverted it defaults to the
+    /// brightest palette color, otherwise to the terminal's background.
+    pub background: Option<Color>,
+    /// Draw the coldest band and the border in the terminal's default colors,
+    /// so the terminal's own background shows through around the flame. Takes
+    /// precedence over `background`.
+    pub transparent: bool,
+    /// Draw every glyph in the terminal's foreground color, so only the glyphs
+    /// show the heat.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig { mono: true, ..FireConfig::default() };
+    /// let mut app = App::new(40, 20).with_config(config).with_seed(6);
+    /// for _ in 0..50 {
+    ///     app.update_fire(TICK_RATE);
+    /// }
+    /// let text = app.render_fire();
+    /// let mut spans = text.lines.iter().flat_map(|line| &line.spans);
+    /// let first = spans.next().unwrap().style;
+    /// assert!(spans.all(|span| span.style == first));
+    /// ```
+    pub mono: bool,
+    /// Mix neighboring palette colors in a fixed pattern where the heat falls
+    /// between them, smoothing the bands of small palettes.
+    pub dither: bool,
+    /// Multiplier on every channel of the drawn colors. 1 leaves them as is.
+    pub brightness: f32,
+    /// How far the drawn colors are pushed away from mid gray, or pulled
+    /// towards it below 1. 1 leaves them as is.
+    pub contrast: f32,
+    /// How much the drawn colors darken towards the corners of the frame,
+    /// from 0 for not at all to 1 for black corners.
+    pub vignette: f32,
+    /// Color the hot base of the flame blue like a gas flame, keeping the
+    /// palette for the cooler cells and the upper rows.
+    pub gas_flame: bool,
+    /// Fraction of the flame height, from the bottom, that `gas_flame` colors
+    /// blue.
+    pub gas_transition: f32,
+    /// Glyphs used to draw the flame.
+    pub char_ramp: CharRamp,
+    /// Resample the glyph ramp to as many bands as the palette has colors, so
+    /// a glyph band always changes together with the color.
+    pub align_bands: bool,
+    /// Pick glyphs from each cell's heat instead of at random every frame.
+    pub glyph_stability: bool,
+    /// How glyphs are picked at random otherwise. `hashed` and `fixed` spare
+    /// drawing a random number for every cell on large frames.
+    pub glyph_variety: GlyphVariety,
+    /// Cells cooler than this keep the glyph that follows their heat rather
+    /// than picking one at random, so the sparse tips hold still while the
+    /// hotter flame shimmers. 0 lets every cell shimmer.
+    pub shimmer_threshold: u8,
+    /// Only re-pick a cell's glyph when its glyph band or color changes, so
+    /// unchanged cells aren't redrawn. Cuts the output on slow links.
+    pub diff_render: bool,
+    /// Fraction of the last drawn heat that lingers into the next frame, for a
+    /// motion blur. Only affects drawing. 0 disables it.
+    pub blur_trail: f32,
+    /// How strongly the hottest cells light up a halo around them. Only
+    /// affects drawing. 0 disables it.
+    pub bloom: f32,
+    /// Tint freshly ignited cells whiter than long-lived ones.
+    pub age_tint: bool,
+    /// Color of the ash left for a moment where a hot cell has just gone out,
+    /// before it turns black. Off when unset.
+    pub ash: Option<Color>,
+    /// Specks that fall over the flame and melt into its hot cells, snow
+    /// cooling them as it goes. Off when unset.
+    pub falling: Option<Falling>,
+    /// Fraction of its own heat a cell keeps each frame. Higher values make
+    /// the flame sluggish, lower ones make it flicker rapidly.
+    ///
+    /// ```
+    /// use fire_in_the_term::{HEAT_SCALE, config::FireConfig, step_grid};
+    /// use rand::{SeedableRng, rngs::StdRng};
+    ///
+    /// // A hot row with nothing rising into it or spreading along it
+    /// let mut grid = vec![vec![0; 40]; 20];
+    /// grid[10] = vec![200 * HEAT_SCALE; 40];
+    /// let [kept, dropped] = [0.5, 0.0].map(|self_weight| {
+    ///     let config = FireConfig {
+    ///         rise_strength: 0.0,
+    ///         side_diffusion: 0.0,
+    ///         self_weight,
+    ///         ..FireConfig::default()
+    ///     };
+    ///     step_grid(&grid, &config, &mut StdRng::seed_from_u64(1))
+    /// });
+    /// assert!(kept[10].iter().all(|&heat| heat >= 50 * HEAT_SCALE));
+    /// // Without its own heat a cell has no more than its flicker
+    /// assert!(dropped[10].iter().all(|&heat| heat <= 15 * HEAT_SCALE));
+    /// ```
+    pub self_weight: f32,
+    /// How much of each newly computed frame is held back, blending every
+    /// cell with its previous heat for a slow, molten motion. 0 takes each
+    /// new frame as is and 1 freezes the flame.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let mut app = App::new(40, 20).with_seed(9);
+    /// for _ in 0..50 {
+    ///     app.update_fire(TICK_RATE);
+    /// }
+    /// let burning = app.grid().to_vec();
+    /// app.config.viscosity = 1.0;
+    /// for _ in 0..20 {
+    ///     app.update_fire(TICK_RATE);
+    ///     assert_eq!(app.grid(), &burning[..]);
+    /// }
+    /// ```
+    pub viscosity: f32,
+    /// Simulation steps taken for each tick, each covering that fraction of
+    /// it, for finer motion at the same frame rate. 1 steps once per tick.
+    ///
+    /// However many substeps there are, each frame advances the fire by the
+    /// same time, on the same grid, and a frame timed a hair over a tick
+    /// takes no extra steps:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// for substeps in [1, 2, 4, 7] {
+    ///     let config = FireConfig { substeps, ..FireConfig::default() };
+    ///     let [mut exact, mut late] =
+    ///         [1, 2].map(|_| App::new(40, 20).with_config(config.clone()).with_seed(3));
+    ///     for _ in 0..10 {
+    ///         exact.update_fire(TICK_RATE);
+    ///         late.update_fire(TICK_RATE.mul_f32(1.001));
+    ///     }
+    ///     assert_eq!(exact.tick_count(), 10);
+    ///     assert_eq!(exact.grid_size(), (40, 20));
+    ///     assert_eq!(exact.grid(), late.grid());
+    /// }
+    /// ```
+    pub substeps: u32,
+    /// Fraction of the heat below a cell that rises into it each frame.
+    ///
+    /// A strong rise carries the flame all the way up to the top row, which
+    /// is drawn like any other:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// for height in [1, 2, 5, 12, 30] {
+    ///     let config = FireConfig { rise_strength: 0.7, ..FireConfig::default() };
+    ///     let mut app = App::new(20, height).with_config(config).with_seed(1);
+    ///     for _ in 0..200 {
+    ///         app.update_fire(TICK_RATE);
+    ///     }
+    ///     assert!(app.grid()[0].iter().any(|&heat| heat > 0), "{height} rows");
+    ///     let top = app.render_plain().lines().next().unwrap().to_string();
+    ///     assert!(top.chars().any(|c| c != ' '), "{height} rows");
+    /// }
+    /// ```
+    pub rise_strength: f32,
+    /// How many cells to either side a cell draws heat from, with closer ones
+    /// counting for more. Higher values smooth the flame into softer shapes
+    /// that burn a little lower. At most 16.
+    pub diffusion_radius: usize,
+    /// Share of the heat each side gives a cell, spread over the
+    /// `diffusion_radius` cells. Higher values widen the flame. At most 0.15.
+    pub side_diffusion: f32,
+    /// Columns over which the flame fades out towards the left and right
+    /// edges, so it tapers instead of being cut off. Only affects drawing. 0
+    /// keeps hard edges.
+    pub edge_falloff: usize,
+    /// Fraction of the grid, from the bottom, the flame may rise into. Cells
+    /// above it are put out every frame. 1 lets it reach the top.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig { max_height: 0.5, rise_strength: 0.6, ..FireConfig::default() };
+    /// let mut app = App::new(60, 20).with_config(config).with_seed(2);
+    /// for _ in 0..200 {
+    ///     app.update_fire(TICK_RATE);
+    ///     assert!(app.grid()[..10].iter().flatten().all(|&heat| heat == 0));
+    /// }
+    /// assert!(app.grid()[10].iter().any(|&heat| heat > 0));
+    /// ```
+    pub max_height: f32,
+    /// How much faster the upper rows cool than the bottom row. The decay of a
+    /// cell is scaled by `1 + tip_decay * (1 - y / height)`, so 0 gives uniform
+    /// decay and higher values taper the flame tips.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// // Heat left in the upper half once the fire has settled
+    /// let upper_heat = |tip_decay| {
+    ///     let config = FireConfig { tip_decay, ..FireConfig::default() };
+    ///     let mut app = App::new(60, 30).with_config(config).with_seed(5);
+    ///     for _ in 0..200 {
+    ///         app.update_fire(TICK_RATE);
+    ///     }
+    ///     app.grid()[..15].iter().flatten().map(|&heat| heat as u64).sum::<u64>()
+    /// };
+    /// assert!(upper_heat(1.0) < upper_heat(0.0));
+    /// ```
+    pub tip_decay: f32,
+    /// How fierce the flame burns, from 0 to 1. The decay is scaled by
+    /// `1.5 - intensity`, so higher values cool it slower and let it reach
+    /// higher. 0.5 is the usual fire. [`App::set_intensity`](crate::App::set_intensity)
+    /// sets it within that range.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let [low, high] = [0.2, 0.8].map(|intensity| {
+    ///     let config = FireConfig { intensity, ..FireConfig::default() };
+    ///     let mut app = App::new(60, 30).with_config(config).with_seed(4);
+    ///     for _ in 0..100 {
+    ///         app.update_fire(TICK_RATE);
+    ///     }
+    ///     app.grid().iter().flatten().map(|&heat| heat as u64).sum::<u64>()
+    /// });
+    /// assert!(high > low);
+    /// ```
+    pub intensity: f32,
+    /// Most extra heat a patch of smooth, rising noise takes away from each
+    /// cell, for wispier, textured flames. 0 keeps the decay even.
+    pub cooling_noise_scale: f32,
+    /// Flicker with smooth noise that drifts over the flame instead of
+    /// picking each cell's flicker at random, so neighbouring cells and
+    /// frames flicker together, like a candle, rather than like static.
+    ///
+    /// ```
+    /// use fire_in_the_term::{config::FireConfig, step_grid};
+    /// use rand::{SeedableRng, rngs::StdRng};
+    ///
+    /// // Nothing rises, is kept or spreads, so each cell above the base is
+    /// // lit only if it flickers up
+    /// let changes = |coherent_flicker| {
+    ///     let config = FireConfig {
+    ///         rise_strength: 0.0,
+    ///         self_weight: 0.0,
+    ///         side_diffusion: 0.0,
+    ///         coherent_flicker,
+    ///         ..FireConfig::default()
+    ///     };
+    ///     let next = step_grid(&vec![vec![0; 40]; 20], &config, &mut StdRng::seed_from_u64(1));
+    ///     next[..19]
+    ///         .iter()
+    ///         .flat_map(|row| row.windows(2).filter(|pair| (pair[0] > 0) != (pair[1] > 0)))
+    ///         .count()
+    /// };
+    /// // Smooth flicker lights cells in runs rather than scattered
+    /// assert!(changes(true) * 3 < changes(false));
+    /// ```
+    pub coherent_flicker: bool,
+    /// Width in cells of the patches that flicker together with
+    /// `coherent_flicker`. Must be above 0.
+    pub flicker_scale: f32,
+    /// How fast the `coherent_flicker` patches change, in patches per
+    /// frame. 0 holds them still.
+    pub flicker_speed: f32,
+    /// Grids shorter than this many rows switch to the compact parameters
+    /// below so a recognizable flame still fits. 0 disables compact mode.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// // Heat a 10-row fire holds above its base once it has settled
+    /// let settled_heat = |compact_threshold| {
+    ///     let config = FireConfig { compact_threshold, ..FireConfig::default() };
+    ///     let mut app = App::new(40, 10).with_config(config).with_seed(3);
+    ///     for _ in 0..200 {
+    ///         app.update_fire(TICK_RATE);
+    ///     }
+    ///     app.grid()[..9].iter().flatten().map(|&heat| heat as u64).sum::<u64>()
+    /// };
+    /// assert!(settled_heat(20) > 0);
+    /// assert!(settled_heat(20) > settled_heat(0));
+    /// ```
+    pub compact_threshold: usize,
+    /// Rise strength used in compact mode instead of `rise_strength`.
+    pub compact_rise_strength: f32,
+    /// Multiplier on the decay in compact mode.
+    pub compact_decay_scale: f32,
+    /// Roughly how many hits a bottom cell takes to heat up, each closing
+    /// `1 / ignition_build_frames` of its gap to a hot value, for a steadier
+    /// base. 0 ignites cells at full heat at once.
+    pub ignition_build_frames: u32,
+    /// Rows between the bottom of the grid and the row the heat is fed into,
+    /// for a flame floating above the bottom. The rows below it stay cold.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig { injection_row_offset: 2, ..FireConfig::default() };
+    /// let mut app = App::new(40, 20).with_config(config).with_seed(5);
+    /// for _ in 0..100 {
+    ///     app.update_fire(TICK_RATE);
+    ///     assert!(app.grid()[18..].iter().flatten().all(|&heat| heat == 0));
+    /// }
+    /// assert!(app.grid()[17].iter().any(|&heat| heat > 0));
+    /// ```
+    pub injection_row_offset: usize,
+    /// How readily each part of the base catches, from 0 to 1, spread evenly
+    /// from the left edge to the right, e.g. to follow the outline of a
+    /// picture. Empty lights the whole base alike.
+    ///
+    /// Where the base never catches, the embers left on it die down from
+    /// their last heat a little each tick, and settle at zero without
+    /// wrapping around:
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, HEAT_SCALE, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig { injection_profile: vec![0.0], ..FireConfig::default() };
+    /// let mut app = App::new(20, 10).with_config(config).with_seed(6);
+    /// let mut grid = vec![vec![0; 20]; 10];
+    /// grid[9] = vec![200 * HEAT_SCALE; 20];
+    /// app.set_grid(&grid);
+    /// app.update_fire(TICK_RATE);
+    /// let cooled = 190 * HEAT_SCALE..=195 * HEAT_SCALE;
+    /// assert!(app.grid()[9].iter().all(|heat| cooled.contains(heat)));
+    /// for _ in 0..60 {
+    ///     app.update_fire(TICK_RATE);
+    ///     assert!(app.grid()[9].iter().all(|&heat| heat < 200 * HEAT_SCALE));
+    /// }
+    /// assert!(app.grid()[9].iter().all(|&heat| heat == 0));
+    /// ```
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub injection_profile: Vec<f32>,
+    /// Rows of text marking where the fire may burn, stretched over the
+    /// frame: any character but a space can hold heat, and the bottom edge of
+    /// each marked region is lit like the base. Empty lets it burn anywhere.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// // Each character covers 10 by 5 cells of a 40x20 grid, so only the
+    /// // middle 20 by 10 cells can burn
+    /// let burn_mask = ["    ", " ## ", " ## ", "    "].map(String::from).to_vec();
+    /// let config = FireConfig { burn_mask, ..FireConfig::default() };
+    /// let mut app = App::new(40, 20).with_config(config).with_seed(4);
+    /// let masked = |x: usize, y: usize| !(10..30).contains(&x) || !(5..15).contains(&y);
+    /// for _ in 0..100 {
+    ///     app.update_fire(TICK_RATE);
+    ///     for (y, row) in app.grid().iter().enumerate() {
+    ///         for (x, &heat) in row.iter().enumerate() {
+    ///             assert!(!masked(x, y) || heat == 0, "({x}, {y}) burns");
+    ///         }
+    ///     }
+    /// }
+    /// assert!(app.grid()[14][10..30].iter().any(|&heat| heat > 0));
+    /// ```
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub burn_mask: Vec<String>,
+    /// How the chance of the base catching falls off from the middle to the
+    /// edges, which sets how wide the base of the flame burns.
+    pub base_curve: BaseCurve,
+    /// Exponent of the `power` curve. The smaller it is, the further out to
+    /// the edges the base burns.
+    pub base_exponent: f32,
+    /// Strength of a chimney draft from 0 to 1, which starves the outermost
+    /// columns and pulls heat in from the sides, narrowing the base into a
+    /// tight column. 0 disables it.
+    pub draft: f32,
+    /// Average number of spontaneous bursts per minute, each shooting a jet of
+    /// flame up from a random spot of the base. 0 disables them.
+    pub flare_rate: f32,
+    /// Beats per minute at which the flame swells and fades. 0 disables it.
+    pub pulse_bpm: f32,
+    /// Times a minute the base of the flame wanders from side to side and
+    /// back, moving where it burns hottest. 0 keeps it in the middle.
+    pub wander: f32,
+    /// Every cell is clamped into `floor_heat..=ceil_heat` after each update,
+    /// keeping a guaranteed glow or preventing full saturation.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, HEAT_SCALE, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig { floor_heat: 40, ceil_heat: 180, ..FireConfig::default() };
+    /// let mut app = App::new(40, 20).with_config(config).with_seed(2);
+    /// for _ in 0..30 {
+    ///     app.update_fire(TICK_RATE);
+    ///     let range = 40 * HEAT_SCALE..=180 * HEAT_SCALE;
+    ///     assert!(app.grid().iter().flatten().all(|heat| range.contains(heat)));
+    /// }
+    /// ```
+    pub floor_heat: u8,
+    pub ceil_heat: u8,
+    /// Cool the fire harder while it is blown out, burning near white all
+    /// over for seconds on end, until it recovers. See [`crate::App::blown_out`].
+    pub auto_dampen: bool,
+    /// Steady wind in cells per row. Positive values blow the flame right.
+    pub wind: f32,
+    /// Make the wind sway back and forth around `wind` on its own.
+    pub auto_wind: bool,
+    /// How far the automatic wind swings to either side, in cells per row.
+    pub wind_amplitude: f32,
+    /// Frames the automatic wind takes to swing back and forth once.
+    pub wind_period: f32,
+    /// Most cells the small, wandering gusts of turbulence shift the rising
+    /// heat sideways, making the flame waver. 0 keeps the rise smooth.
+    pub turbulence: f32,
+    /// Draw every cell two characters wide, halving the simulated width.
+    pub wide: bool,
+    /// Draw every cell as a braille character of 2x4 dots, simulating the
+    /// grid at twice the width for finer detail.
+    pub braille: bool,
+    /// Turn the drawn frame a quarter turn, so the flame burns from the left
+    /// edge towards the right, e.g. for a tall, narrow sidebar.
+    pub rotate: bool,
+    /// Join the left and right edges, so heat spreading or blown off one side
+    /// comes back in at the other, for a fire that tiles seamlessly.
+    ///
+    /// ```
+    /// use fire_in_the_term::{HEAT_SCALE, config::FireConfig, step_grid};
+    /// use rand::{SeedableRng, rngs::StdRng};
+    ///
+    /// // Nothing rises or is kept, so heat only spreads in from the sides
+    /// let mut grid = vec![vec![0; 40]; 20];
+    /// for row in &mut grid {
+    ///     row[39] = 255 * HEAT_SCALE;
+    /// }
+    /// let [walled, wrapped] = [false, true].map(|wrap| {
+    ///     let config = FireConfig {
+    ///         rise_strength: 0.0,
+    ///         self_weight: 0.0,
+    ///         side_diffusion: 0.15,
+    ///         wrap,
+    ///         ..FireConfig::default()
+    ///     };
+    ///     step_grid(&grid, &config, &mut StdRng::seed_from_u64(1))
+    /// });
+    /// // The left edge only warms when it touches the hot right edge
+    /// let left_edge = |grid: &[Vec<_>]| grid[..19].iter().map(|row| row[0] as u32).sum::<u32>();
+    /// assert!(left_edge(&wrapped) > left_edge(&walled));
+    /// ```
+    pub wrap: bool,
+    /// Confine the flame to a silhouette. `None` lets it fill the screen.
+    pub shape: Option<FlameShape>,
+    /// Change where the flame is lit and how it moves, e.g. two fires that
+    /// merge. `None` burns a single fire.
+    pub effect: Option<Effect>,
+    /// Order the rows are worked out in each frame. Only changes which random
+    /// numbers fall on which cell, so the same seed burns differently.
+    pub update_order: UpdateOrder,
+    /// Work out each cell's random numbers from its position, counted from
+    /// the bottom left, the step and the seed, rather than drawing them in
+    /// turn from one stream. A seed then gives every cell the same numbers
+    /// whatever the grid's size, so resizing doesn't reshuffle them.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig { cell_seeding: true, ..FireConfig::default() };
+    /// let [mut narrow, mut wide] =
+    ///     [40, 60].map(|width| App::new(width, 20).with_config(config.clone()).with_seed(7));
+    /// narrow.update_fire(TICK_RATE);
+    /// wide.update_fire(TICK_RATE);
+    /// // The first flickers above the base agree wherever the grids overlap
+    /// for (narrow_row, wide_row) in narrow.grid()[..19].iter().zip(&wide.grid()[..19]) {
+    ///     assert_eq!(narrow_row[..], wide_row[..40]);
+    /// }
+    /// ```
+    pub cell_seeding: bool,
+    /// Rows below the flame used to draw its reflection in water.
+    pub reflect_rows: usize,
+    /// Fires drawn one behind the other, counting the front one, each dimmer
+    /// and slower than the one in front of it for a sense of depth. From 1,
+    /// for just the flame, to 4.
+    pub layers: usize,
+    /// Simulate on a fixed `(width, height)` grid and interpolate it up to the
+    /// screen size, at least 1 by 1. `None` simulates at the screen
+    /// resolution.
+    ///
+    /// ```
+    /// use fire_in_the_term::{App, TICK_RATE, config::FireConfig};
+    ///
+    /// let config = FireConfig { sim_size: Some((5, 0)), ..FireConfig::default() };
+    /// assert!(config.validate().is_err());
+    /// // An app given it anyway simulates on at least one row
+    /// let mut app = App::new(80, 24).with_config(config);
+    /// app.update_fire(TICK_RATE);
+    /// assert_eq!(app.grid_size(), (5, 1));
+    /// ```
+    pub sim_size: Option<(usize, usize)>,
+    /// Flames wider than this many columns are simulated at half resolution in
+    /// each direction and scaled up, which keeps frames quick on ultrawide
+    /// terminals. 0 always simulates at full resolution.
+    pub ultrawide_columns: usize,
+    /// Keys to use for actions instead of their defaults, e.g. `quit = "esc"`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<Action, Key>,
+}
+
+impl Default for FireConfig {
+    fn default() -> Self {
+        FireConfig {
+            palette: palette::FIRE.to_vec(),
+            palette_fade_frames: 15,
+            invert: false,
+            background: None,
+            transparent: false,
+            mono: false,
+            dither: false,
+            brightness: 1.0,
+            contrast: 1.0,
+            vignette: 0.0,
+            gas_flame: false,
+            gas_transition: 0.3,
+            char_ramp: CharRamp::default(),
+            align_bands: false,
+            glyph_stability: false,
+            glyph_variety: GlyphVariety::Random,
+            shimmer_threshold: 0,
+            diff_render: false,
+            blur_trail: 0.0,
+            bloom: 0.0,
+            age_tint: false,
+            ash: None,
+            falling: None,
+            self_weight: 1.0 / 3.0,
+            viscosity: 0.0,
+            substeps: 1,
+            rise_strength: 0.5,
+            diffusion_radius: 1,
+            side_diffusion: 0.125,
+            edge_falloff: 0,
+            max_height: 1.0,
+            tip_decay: 0.0,
+            intensity: 0.5,
+            cooling_noise_scale: 0.0,
+            coherent_flicker: false,
+            flicker_scale: 4.0,
+            flicker_speed: 0.25,
+            compact_threshold: 20,
+            compact_rise_strength: 0.52,
+            compact_decay_scale: 0.85,
+            ignition_build_frames: 0,
+            injection_row_offset: 0,
+            injection_profile: Vec::new(),
+            burn_mask: Vec::new(),
+            base_curve: BaseCurve::Power,
+            base_exponent: 0.2,
+            draft: 0.0,
+            flare_rate: 0.0,
+            pulse_bpm: 0.0,
+            wander: 0.0,
+            floor_heat: 0,
+            ceil_heat: 255,
+            auto_dampen: false,
+            wind: 0.0,
+            auto_wind: false,
+            wind_amplitude: 0.6,
+            wind_period: 300.0,
+            turbulence: 0.0,
+            wide: false,
+            braille: false,
+            rotate: false,
+            wrap: false,
+            shape: None,
+            effect: None,
+            update_order: UpdateOrder::BottomUp,
+            cell_seeding: false,
+            reflect_rows: 0,
+            layers: 1,
+            sim_size: None,
+            ultrawide_columns: 400,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+/// Specks that drift down over the flame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Falling {
+    /// White flakes that cool the flame where they melt
+    Snow,
+    /// Gray flecks that just burn up
+    Ash,
+}
+
+impl Falling {
+    pub fn name(self) -> &'static str {
+        match self {
+            Falling::Snow => "snow",
+            Falling::Ash => "ash",
+        }
+    }
+
+    /// Returns what falls after `falling`, from nothing to snow and ash and
+    /// back to nothing.
+    pub fn next(falling: Option<Falling>) -> Option<Falling> {
+        match falling {
+            None => Some(Falling::Snow),
+            Some(Falling::Snow) => Some(Falling::Ash),
+            Some(Falling::Ash) => None,
+        }
+    }
+}
+
+/// How the chance of the base catching falls off from the middle of the base
+/// to its edges.
+///
+/// ```
+/// use fire_in_the_term::config::BaseCurve;
+///
+/// // Columns across a base of 80 where the base is more likely than not to
+/// // catch
+/// let width = |curve: BaseCurve| {
+///     (0..80)
+///         .filter(|&x| curve.weight(1.0 - (x as f32 - 40.0).abs() / 40.0, 0.2) > 0.5)
+///         .count()
+/// };
+/// assert!(width(BaseCurve::Power) > width(BaseCurve::Cosine));
+/// assert!(width(BaseCurve::Cosine) > width(BaseCurve::Linear));
+/// assert!(width(BaseCurve::Linear) > width(BaseCurve::Gaussian));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BaseCurve {
+    /// Raised to `base_exponent`, burning almost evenly out to the edges at
+    /// the default 0.2
+    #[default]
+    Power,
+    /// A bell curve, for a base burning in a narrow mound
+    Gaussian,
+    /// Falling off steadily from the middle
+    Linear,
+    /// A quarter cosine, round in the middle and falling off at the edges
+    Cosine,
+}
+
+/// Spread of the `gaussian` curve, as a share of the half width of the base.
+const GAUSSIAN_SPREAD: f32 = 0.35;
+
+impl BaseCurve {
+    /// Returns how likely a cell of the base is to catch, from 0 to 1, given
+    /// how near it is to the middle, from 1 there to 0 at the edges.
+    /// `exponent` only shapes the `power` curve.
+    pub fn weight(self, nearness: f32, exponent: f32) -> f32 {
+        match self {
+            BaseCurve::Power => nearness.powf(exponent),
+            BaseCurve::Gaussian if nearness <= 0.0 => 0.0,
+            BaseCurve::Gaussian => {
+                (-(1.0 - nearness).powi(2) / (2.0 * GAUSSIAN_SPREAD * GAUSSIAN_SPREAD)).exp()
+            }
+            BaseCurve::Linear => nearness,
+            BaseCurve::Cosine => ((1.0 - nearness) * std::f32::consts::FRAC_PI_2)
+                .cos()
+                .max(0.0),
+        }
+    }
+}
+
+/// Order the rows of the grid are worked out in. Every row is worked out
+/// from the last frame, so heat rises a row a frame either way.
+///
+/// ```
+/// use fire_in_the_term::{HEAT_SCALE, config::{FireConfig, UpdateOrder}, step_grid};
+/// use rand::{SeedableRng, rngs::StdRng};
+///
+/// let mut embers = vec![vec![0; 16]; 8];
+/// embers[7] = vec![255 * HEAT_SCALE; 16];
+/// let [bottom_up, top_down] = [UpdateOrder::BottomUp, UpdateOrder::TopDown].map(|order| {
+///     let config = FireConfig { update_order: order, ..FireConfig::default() };
+///     step_grid(&embers, &config, &mut StdRng::seed_from_u64(1))
+/// });
+/// // The same seed lands differently on the row above the embers
+/// assert_ne!(bottom_up[6], top_down[6]);
+/// // But in both the heat has only risen one row, leaving faint sparks above
+/// for next in [bottom_up, top_down] {
+///     assert!(next[..6].iter().flatten().all(|&heat| heat < 20 * HEAT_SCALE));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum UpdateOrder {
+    /// From the embers up to the top
+    #[default]
+    BottomUp,
+    /// From the top down to the embers
+    TopDown,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Palette(PaletteError),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{err}"),
+            ConfigError::Parse(err) => write!(f, "{err}"),
+            ConfigError::Palette(err) => write!(f, "invalid palette: {err}"),
+            ConfigError::Invalid(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(err: toml::de::Error) -> Self {
+        ConfigError::Parse(err)
+    }
+}
+
+impl FireConfig {
+    /// Reads a config from a TOML file.
+    pub fn load(path: &Path) -> Result<FireConfig, ConfigError> {
+        let config: FireConfig = toml::from_str(&fs::read_to_string(path)?)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the values that can't be expressed through the field types.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        palette::validate(&self.palette).map_err(ConfigError::Palette)?;
+        if self.floor_heat > self.ceil_heat {
+            return Err(ConfigError::Invalid(format!(
+                "floor_heat ({}) must not exceed ceil_heat ({})",
+                self.floor_heat, self.ceil_heat
+            )));
+        }
+        if self.brightness < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "brightness ({}) must not be negative",
+                self.brightness
+            )));
+        }
+        if self.contrast < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "contrast ({}) must not be negative",
+                self.contrast
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.vignette) {
+            return Err(ConfigError::Invalid(format!(
+                "vignette ({}) must be between 0 and 1",
+                self.vignette
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.self_weight) {
+            return Err(ConfigError::Invalid(format!(
+                "self_weight ({}) must be between 0 and 1",
+                self.self_weight
+            )));
+        }
+        if !(1..=4).contains(&self.layers) {
+            return Err(ConfigError::Invalid(format!(
+                "layers ({}) must be from 1 to 4",
+                self.layers
+            )));
+        }
+        if self.diffusion_radius > 16 {
+            return Err(ConfigError::Invalid(format!(
+                "diffusion_radius ({}) must be at most 16",
+                self.diffusion_radius
+            )));
+        }
+        if !(0.0..=0.15).contains(&self.side_diffusion) {
+            return Err(ConfigError::Invalid(format!(
+                "side_diffusion ({}) must be between 0 and 0.15",
+                self.side_diffusion
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.max_height) {
+            return Err(ConfigError::Invalid(format!(
+                "max_height ({}) must be between 0 and 1",
+                self.max_height
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.draft) {
+            return Err(ConfigError::Invalid(format!(
+                "draft ({}) must be between 0 and 1",
+                self.draft
+            )));
+        }
+        if self.flare_rate < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "flare_rate ({}) must not be negative",
+                self.flare_rate
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.viscosity) {
+            return Err(ConfigError::Invalid(format!(
+                "viscosity ({}) must be between 0 and 1",
+                self.viscosity
+            )));
+        }
+        if let Some(weight) =
+            (self.injection_profile.iter()).find(|weight| !(0.0..=1.0).contains(*weight))
+        {
+            return Err(ConfigError::Invalid(format!(
+                "injection_profile weights ({weight}) must be between 0 and 1"
+            )));
+        }
+        if !self.burn_mask.is_empty() && self.burn_mask.iter().all(|row| row.trim().is_empty()) {
+            return Err(ConfigError::Invalid(
+                "burn_mask has no cells that can burn".to_string(),
+            ));
+        }
+        if !(1..=16).contains(&self.substeps) {
+            return Err(ConfigError::Invalid(format!(
+                "substeps ({}) must be between 1 and 16",
+                self.substeps
+            )));
+        }
+        if self.pulse_bpm < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "pulse_bpm ({}) must not be negative",
+                self.pulse_bpm
+            )));
+        }
+        if self.base_exponent < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "base_exponent ({}) must not be negative",
+                self.base_exponent
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.intensity) {
+            return Err(ConfigError::Invalid(format!(
+                "intensity ({}) must be between 0 and 1",
+                self.intensity
+            )));
+        }
+        if self.flicker_scale <= 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "flicker_scale ({}) must be above 0",
+                self.flicker_scale
+            )));
+        }
+        if self.flicker_speed < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "flicker_speed ({}) must not be negative",
+                self.flicker_speed
+            )));
+        }
+        if let Some((width, height)) = self.sim_size
+            && (width == 0 || height == 0)
+        {
+            return Err(ConfigError::Invalid(format!(
+                "sim_size ({width}x{height}) must be at least 1x1"
+            )));
+        }
+        if self.wander < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "wander ({}) must not be negative",
+                self.wander
+            )));
+        }
+        if !(0.0..1.0).contains(&self.blur_trail) {
+            return Err(ConfigError::Invalid(format!(
+                "blur_trail ({}) must be at least 0 and below 1",
+                self.blur_trail
+            )));
+        }
+        if self.bloom < 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "bloom ({}) must not be negative",
+                self.bloom
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.gas_transition) {
+            return Err(ConfigError::Invalid(format!(
+                "gas_transition ({}) must be between 0 and 1",
+                self.gas_transition
+            )));
+        }
+        if self.wind_period <= 0.0 {
+            return Err(ConfigError::Invalid(format!(
+                "wind_period ({}) must be positive",
+                self.wind_period
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the background color to paint behind the flame, if any.
+    pub fn background(&self) -> Option<Color> {
+        if self.transparent {
+            return None;
+        }
+        self.background
+            .or_else(|| self.invert.then(|| self.palette[self.palette.len() - 1]))
+    }
+
+    /// Changes the setting `key` to `value` as it would be written in a config
+    /// file, e.g. `wind` to `0.5`. A `palette` can also be the name of a
+    /// built-in one, such as `"ice"`. The config is left as it was if the
+    /// result doesn't validate.
+    ///
+    /// ```
+    /// use fire_in_the_term::{config::FireConfig, palette};
+    ///
+    /// let mut config = FireConfig::default();
+    /// config.set("wind", toml::Value::Float(0.5)).unwrap();
+    /// config.set("palette", toml::Value::String("ice".into())).unwrap();
+    /// assert_eq!(config.wind, 0.5);
+    /// assert_eq!(config.palette, palette::ICE);
+    /// assert!(config.set("viscosity", toml::Value::Float(2.0)).is_err());
+    /// ```
+    pub fn set(&mut self, key: &str, value: toml::Value) -> Result<(), ConfigError> {
+        let preset = (key == "palette")
+            .then(|| value.as_str())
+            .flatten()
+            .and_then(|name| Preset::ALL.into_iter().find(|preset| preset.name() == name));
+        if let Some(preset) = preset {
+            self.palette = preset.colors().to_vec();
+            return Ok(());
+        }
+
+        let mut table =
+            toml::Table::try_from(&*self).expect("config is always representable as TOML");
+        table.insert(key.to_string(), value);
+        let config: FireConfig = toml::Value::Table(table).try_into()?;
+        config.validate()?;
+        *self = config;
+        Ok(())
+    }
+
+    /// Serializes the config as TOML that [`FireConfig::load`] accepts.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("config is always representable as TOML")
+    }
+}
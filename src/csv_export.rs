@@ -0,0 +1,103 @@
+use fire_in_the_term::{App, Heat, TICK_RATE};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Simulates `frames` frames of `app` and writes their heat grids to `path`
+/// as CSV, separating frames with a blank line.
+pub fn export(app: &mut App, path: &Path, frames: usize) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    for frame in 0..frames {
+        if frame > 0 {
+            writeln!(out)?;
+        }
+        write_grid_csv(&mut out, app.grid())?;
+        app.update_fire(TICK_RATE);
+    }
+    out.flush()
+}
+
+/// Reads a heat grid written by [`write_grid_csv`], such as a frame of a
+/// `--dump-csv` file, up to the first blank line. Every row has to hold as
+/// many values as the first, and values beyond the heat range are clamped
+/// into it.
+pub fn read_grid_csv(contents: &str) -> Result<Vec<Vec<Heat>>, String> {
+    let mut grid: Vec<Vec<Heat>> = Vec::new();
+    for (number, line) in (1..).zip(contents.lines()) {
+        if line.trim().is_empty() {
+            break;
+        }
+        let row = (line.split(','))
+            .map(|value| {
+                let heat = value.trim().parse::<i64>().map_err(|_| {
+                    format!("line {number}: '{}' is not a heat value", value.trim())
+                })?;
+                Ok(heat.clamp(0, Heat::MAX as i64) as Heat)
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if let Some(first) = grid.first()
+            && row.len() != first.len()
+        {
+            return Err(format!(
+                "line {number} has {} values where the first has {}",
+                row.len(),
+                first.len()
+            ));
+        }
+        grid.push(row);
+    }
+    if grid.is_empty() {
+        return Err("no grid rows".to_string());
+    }
+    Ok(grid)
+}
+
+/// Writes one row of comma-separated heat values per grid row, top first.
+pub fn write_grid_csv(out: &mut impl Write, grid: &[Vec<Heat>]) -> io::Result<()> {
+    for row in grid {
+        let mut values = row.iter();
+        if let Some(first) = values.next() {
+            write!(out, "{first}")?;
+        }
+        for value in values {
+            write!(out, ",{value}")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_round_trip_through_csv() {
+        let mut app = App::new(30, 12).with_seed(8);
+        for _ in 0..40 {
+            app.update_fire(TICK_RATE);
+        }
+        let mut csv = Vec::new();
+        write_grid_csv(&mut csv, app.grid()).unwrap();
+        // Only the first frame of a dump is read
+        csv.extend_from_slice(b"\n1,2,3\n");
+        let grid = read_grid_csv(&String::from_utf8(csv).unwrap()).unwrap();
+        assert_eq!(grid, app.grid());
+
+        let mut restored = App::new(30, 12);
+        restored.set_grid(&grid);
+        assert_eq!(restored.grid(), app.grid());
+    }
+
+    #[test]
+    fn bad_grids_are_clamped_or_rejected() {
+        let max = Heat::MAX as i64;
+        let grid = read_grid_csv(&format!("-5, 7, {}\n", max + 1)).unwrap();
+        assert_eq!(grid, [[0, 7, Heat::MAX]]);
+        assert!(read_grid_csv("1,2\n3\n").unwrap_err().contains("line 2"));
+        assert!(read_grid_csv("1,hot\n").unwrap_err().contains("'hot'"));
+        assert!(read_grid_csv("\n1,2\n").is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use fire_in_the_term::App;
+use std::time::Duration;
+
+/// Where `--cycle` is in its loop.
+enum Phase {
+    Burning,
+    // The base is out and the flame is dying down
+    BurningDown,
+    Dark,
+}
+
+/// Lets the fire burn for a while, puts it out, keeps it dark for a moment
+/// once it has died down and then lights it again, over and over, for
+/// `--cycle`. Time only passes on frames that are shown, so pausing holds
+/// the cycle too.
+pub struct Cycle {
+    burn: Duration,
+    dark: Duration,
+    phase: Phase,
+    // Time spent in the current phase
+    elapsed: Duration,
+}
+
+impl Cycle {
+    pub fn new(burn: Duration, dark: Duration) -> Cycle {
+        Cycle {
+            burn,
+            dark,
+            phase: Phase::Burning,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Moves the cycle on by a frame `interval` long, putting out or lighting
+    /// `apps` as it changes phase.
+    pub fn advance(&mut self, apps: &mut [App], interval: Duration) {
+        self.elapsed += interval;
+        let next = match self.phase {
+            Phase::Burning if self.elapsed >= self.burn => Phase::BurningDown,
+            // However long the flame takes to burn down, the dark time only
+            // starts once it is gone
+            Phase::BurningDown if apps.iter().all(App::is_out) => Phase::Dark,
+            Phase::Dark if self.elapsed >= self.dark => Phase::Burning,
+            _ => return,
+        };
+        for app in apps.iter_mut() {
+            app.set_lit(matches!(next, Phase::Burning));
+        }
+        self.phase = next;
+        self.elapsed = Duration::ZERO;
+    }
+}
@@ -0,0 +1,124 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Strongest sideways pull of `Effect::Twin`, in cells per row, reached at
+/// the top of the outer edges.
+const TWIN_PULL: f32 = 1.5;
+
+/// Center of `Effect::Fireball`, as a fraction of the grid's height from the
+/// top, and its radius as a fraction of the height, or most of the width on
+/// grids too narrow for that.
+const FIREBALL_CENTER: f32 = 0.55;
+const FIREBALL_RADIUS: f32 = 0.38;
+/// Inside this fraction of its radius the fireball keeps all its heat, which
+/// fades out towards the rim from there.
+const FIREBALL_CORE: f32 = 0.7;
+/// Chance each frame that a cell in the lower half of the core catches.
+const FIREBALL_IGNITION: f64 = 0.5;
+
+/// Effects that change where the flame is lit and how it moves, on top of
+/// the usual physics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Effect {
+    /// Two fires side by side, each drawn towards the middle by its own
+    /// wind, so they lean into each other and merge into one blaze
+    Twin,
+    /// A round ball of fire floating in the middle, lit from inside rather
+    /// than from the base
+    Fireball,
+}
+
+impl Effect {
+    /// The effect's name as given to `--effect`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Twin => "twin",
+            Effect::Fireball => "fireball",
+        }
+    }
+
+    /// Returns the effect after `effect`, from the plain fire to each effect
+    /// in turn and back to the plain fire.
+    pub fn next(effect: Option<Effect>) -> Option<Effect> {
+        match effect {
+            None => Some(Effect::Twin),
+            Some(Effect::Twin) => Some(Effect::Fireball),
+            Some(Effect::Fireball) => None,
+        }
+    }
+
+    /// How close column `x` of a `width` wide base is to the middle of a
+    /// source of heat, from 1 there to 0 at the source's edges and beyond.
+    pub fn source_bias(self, x: usize, width: usize) -> f32 {
+        let across = (x as f32 + 0.5) / width as f32;
+        match self {
+            Effect::Twin => {
+                // Sources centered a quarter of the way in from each side
+                let distance = (across - 0.25).abs().min((across - 0.75).abs());
+                (1.0 - distance / 0.2).max(0.0)
+            }
+            // Lit from inside instead
+            Effect::Fireball => 0.0,
+        }
+    }
+
+    /// Chance each frame that the cell at `(x, y)` of a `width` by `height`
+    /// grid catches, for effects lit away from the base. `aspect` is how many
+    /// times taller than wide each cell is drawn, so round shapes stay round.
+    pub fn ignition(self, x: usize, y: usize, width: usize, height: usize, aspect: f32) -> f64 {
+        match self {
+            Effect::Twin => 0.0,
+            Effect::Fireball => {
+                let (distance, below_center) = fireball_distance(x, y, width, height, aspect);
+                if below_center && distance < FIREBALL_CORE {
+                    FIREBALL_IGNITION
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Fraction of its heat the cell at `(x, y)` keeps each frame, 1 wherever
+    /// the effect leaves the flame alone. Takes `aspect` like
+    /// [`Effect::ignition`].
+    pub fn reach(self, x: usize, y: usize, width: usize, height: usize, aspect: f32) -> f32 {
+        match self {
+            Effect::Twin => 1.0,
+            Effect::Fireball => {
+                let (distance, _) = fireball_distance(x, y, width, height, aspect);
+                let t = ((distance - FIREBALL_CORE) / (1.0 - FIREBALL_CORE)).clamp(0.0, 1.0);
+                // Smoothstep, so the rim fades out softly
+                1.0 - t * t * (3.0 - 2.0 * t)
+            }
+        }
+    }
+
+    /// Wind the effect blows at `(x, y)` of a `width` by `height` grid, with
+    /// row 0 at the top, in cells per row. Positive winds blow right.
+    pub fn wind(self, x: usize, y: usize, width: usize, height: usize) -> f32 {
+        match self {
+            Effect::Twin => {
+                // Towards the middle from either side, harder further out and
+                // higher up, so the tips meet while the bases stay apart
+                let towards_middle =
+                    ((1.0 - (x as f32 + 0.5) / width as f32 * 2.0) * 16.0).clamp(-1.0, 1.0);
+                let up = 1.0 - (y as f32 + 0.5) / height as f32;
+                TWIN_PULL * towards_middle * up
+            }
+            Effect::Fireball => 0.0,
+        }
+    }
+}
+
+/// How far `(x, y)` is from the fireball's center as a fraction of its
+/// radius, measured as drawn, and whether it is below the center.
+fn fireball_distance(x: usize, y: usize, width: usize, height: usize, aspect: f32) -> (f32, bool) {
+    let center_x = width as f32 / 2.0;
+    let center_y = height as f32 * FIREBALL_CENTER;
+    let radius = (height as f32 * aspect * FIREBALL_RADIUS).min(width as f32 * 0.45);
+    let dx = x as f32 + 0.5 - center_x;
+    let dy = (y as f32 + 0.5 - center_y) * aspect;
+    ((dx * dx + dy * dy).sqrt() / radius, dy > 0.0)
+}
@@ -0,0 +1,70 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Writes a JSON line for every change made while the fire runs, for
+/// `--events-json`, e.g.
+/// `{"time":1760000000.123,"event":"palette_next","value":"palette: ice"}`.
+/// Write errors can't be shown on the live screen, so the first one is kept
+/// and printed to stderr on drop.
+pub struct EventLog {
+    out: BufWriter<File>,
+    error: Option<io::Error>,
+}
+
+impl EventLog {
+    pub fn create(path: &Path) -> io::Result<EventLog> {
+        Ok(EventLog {
+            out: BufWriter::new(File::create(path)?),
+            error: None,
+        })
+    }
+
+    /// Records that `event` happened, leaving things at `value`.
+    pub fn log(&mut self, event: &str, value: &str) {
+        if self.error.is_some() {
+            return;
+        }
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |since_epoch| since_epoch.as_secs_f64());
+        let line = format!(
+            "{{\"time\":{time:.3},\"event\":{},\"value\":{}}}",
+            json_string(event),
+            json_string(value)
+        );
+        // Flushed line by line so the events can be followed as they come
+        if let Err(err) = writeln!(self.out, "{line}").and_then(|()| self.out.flush()) {
+            self.error = Some(err);
+        }
+    }
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        if let Some(err) = &self.error {
+            eprintln!("Failed to write the events: {err}");
+        }
+    }
+}
+
+/// Quotes `value` as a JSON string.
+pub fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
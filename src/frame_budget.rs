@@ -0,0 +1,94 @@
+use fire_in_the_term::palette::ColorMode;
+use ratatui::{buffer::Buffer, style::Color};
+
+/// Frames of unspent budget that can be saved up for a busier frame.
+const BURST_FRAMES: f64 = 4.0;
+
+/// Bytes of the escape sequence moving the cursor to a cell.
+const CURSOR_MOVE_BYTES: usize = 8;
+
+/// Holds the output to the terminal to about `--max-bytes-per-frame` a frame
+/// on average, for slow links. A frame over the budget is first drawn with
+/// fewer colors, whose escape sequences are shorter, and then skipped if it
+/// still doesn't fit. Unspent budget carries over for a few frames, so a
+/// skipped frame soon makes way for the next.
+pub struct FrameBudget {
+    bytes: usize,
+    // Bytes that can be sent right now. Goes negative after a frame that only
+    // went out because enough budget had been saved up.
+    credit: f64,
+    // What the terminal is showing, to work out what each frame changes
+    shown: Buffer,
+}
+
+impl FrameBudget {
+    pub fn new(bytes: usize) -> FrameBudget {
+        FrameBudget {
+            bytes,
+            credit: 0.0,
+            shown: Buffer::default(),
+        }
+    }
+
+    /// Cuts a freshly drawn frame down to the budget, reducing its colors or
+    /// putting back the frame already on screen.
+    pub fn fit(&mut self, next: &mut Buffer) {
+        // The terminal is cleared when its size changes, so everything is
+        // redrawn and the frame can't be skipped
+        let resized = self.shown.area != next.area;
+        if resized {
+            self.shown = Buffer::empty(next.area);
+        }
+        let mut cost = estimate(&self.shown, next);
+        for mode in [ColorMode::Ansi256, ColorMode::Ansi16] {
+            if cost <= self.bytes {
+                break;
+            }
+            for cell in &mut next.content {
+                cell.fg = mode.reduce(cell.fg);
+                cell.bg = mode.reduce(cell.bg);
+            }
+            cost = estimate(&self.shown, next);
+        }
+
+        let most_credit = self.bytes as f64 * BURST_FRAMES;
+        self.credit = (self.credit + self.bytes as f64).min(most_credit);
+        if resized || cost as f64 <= self.credit || self.credit >= most_credit {
+            self.credit -= cost as f64;
+            self.shown.clone_from(next);
+        } else {
+            next.clone_from(&self.shown);
+        }
+    }
+}
+
+/// Roughly how many bytes the terminal is sent to turn `previous` into
+/// `next`, counted the way the crossterm backend writes them: a cursor move
+/// before each run of changed cells, colors wherever they change and the
+/// glyphs themselves.
+fn estimate(previous: &Buffer, next: &Buffer) -> usize {
+    let mut bytes = 0;
+    let mut last_position = None;
+    let mut colors = (Color::Reset, Color::Reset);
+    for (x, y, cell) in previous.diff(next) {
+        if last_position.is_none_or(|(last_x, last_y)| last_y != y || last_x + 1 != x) {
+            bytes += CURSOR_MOVE_BYTES;
+        }
+        if (cell.fg, cell.bg) != colors {
+            bytes += color_bytes(cell.fg) + color_bytes(cell.bg);
+            colors = (cell.fg, cell.bg);
+        }
+        bytes += cell.symbol().len();
+        last_position = Some((x, y));
+    }
+    bytes
+}
+
+/// Bytes of the escape sequence setting a foreground or background color.
+fn color_bytes(color: Color) -> usize {
+    match color {
+        Color::Rgb(..) => 19,
+        Color::Indexed(_) => 11,
+        _ => 5,
+    }
+}
@@ -0,0 +1,66 @@
+use crate::pixels;
+use fire_in_the_term::{App, TICK_RATE};
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use std::{fmt, fs::File, io, path::Path};
+
+/// Quantization speed passed to the encoder, from 1 (best) to 30 (fastest).
+const QUANTIZE_SPEED: i32 = 10;
+
+#[derive(Debug)]
+pub enum GifError {
+    Io(io::Error),
+    Encoding(EncodingError),
+    TooLarge { width: usize, height: usize },
+}
+
+impl fmt::Display for GifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GifError::Io(err) => write!(f, "{err}"),
+            GifError::Encoding(err) => write!(f, "{err}"),
+            GifError::TooLarge { width, height } => {
+                write!(f, "a {width}x{height} image is too large for a GIF")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GifError {}
+
+impl From<io::Error> for GifError {
+    fn from(err: io::Error) -> Self {
+        GifError::Io(err)
+    }
+}
+
+impl From<EncodingError> for GifError {
+    fn from(err: EncodingError) -> Self {
+        GifError::Encoding(err)
+    }
+}
+
+/// Simulates `frames` frames of `app` and writes them to `path` as a looping
+/// animated GIF played back at the normal frame rate.
+pub fn export(app: &mut App, path: &Path, frames: usize) -> Result<(), GifError> {
+    let (pixel_width, pixel_height) = pixels::image_size(app);
+    let too_large = || GifError::TooLarge {
+        width: pixel_width,
+        height: pixel_height,
+    };
+    let width = u16::try_from(pixel_width).map_err(|_| too_large())?;
+    let height = u16::try_from(pixel_height).map_err(|_| too_large())?;
+
+    let mut encoder = Encoder::new(File::create(path)?, width, height, &[])?;
+    encoder.set_repeat(Repeat::Infinite)?;
+    // GIF delays are in hundredths of a second
+    let delay = (TICK_RATE.as_millis() / 10) as u16;
+
+    for _ in 0..frames {
+        let pixels = pixels::render_pixels(app, pixel_width, pixel_height);
+        let mut frame = Frame::from_rgb_speed(width, height, &pixels, QUANTIZE_SPEED);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+        app.update_fire(TICK_RATE);
+    }
+    Ok(())
+}
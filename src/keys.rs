@@ -0,0 +1,207 @@
+use crossterm::event::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Something a key can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Pause,
+    Freeze,
+    HardFreeze,
+    SaveConfig,
+    PersistConfig,
+    AgeTint,
+    GlyphStability,
+    Replay,
+    RampNext,
+    BlocksToggle,
+    SafeGlyphs,
+    AlignNext,
+    PaletteNext,
+    GrayView,
+    FallingNext,
+    EffectNext,
+    Flip,
+    Flare,
+    Relight,
+    WindLeft,
+    WindRight,
+    SelfWeightDown,
+    SelfWeightUp,
+    SideDiffusionDown,
+    SideDiffusionUp,
+    IntensityDown,
+    IntensityUp,
+    Randomize,
+    /// Only available while paused, or already zoomed in.
+    ZoomIn,
+    ZoomOut,
+    PanUp,
+    PanDown,
+    /// Only available with `--debug`.
+    HeatView,
+    /// Only available with `--debug`.
+    WindView,
+}
+
+impl Action {
+    pub const ALL: [Action; 35] = [
+        Action::Quit,
+        Action::Pause,
+        Action::Freeze,
+        Action::HardFreeze,
+        Action::SaveConfig,
+        Action::PersistConfig,
+        Action::AgeTint,
+        Action::GlyphStability,
+        Action::Replay,
+        Action::RampNext,
+        Action::BlocksToggle,
+        Action::SafeGlyphs,
+        Action::AlignNext,
+        Action::PaletteNext,
+        Action::GrayView,
+        Action::FallingNext,
+        Action::EffectNext,
+        Action::Flip,
+        Action::Flare,
+        Action::Relight,
+        Action::WindLeft,
+        Action::WindRight,
+        Action::SelfWeightDown,
+        Action::SelfWeightUp,
+        Action::SideDiffusionDown,
+        Action::SideDiffusionUp,
+        Action::IntensityDown,
+        Action::IntensityUp,
+        Action::Randomize,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::PanUp,
+        Action::PanDown,
+        Action::HeatView,
+        Action::WindView,
+    ];
+
+    fn default_key(self) -> KeyCode {
+        match self {
+            Action::Quit => KeyCode::Char('q'),
+            Action::Pause => KeyCode::Char(' '),
+            Action::Freeze => KeyCode::Char('f'),
+            Action::HardFreeze => KeyCode::Char('F'),
+            Action::SaveConfig => KeyCode::Char('w'),
+            Action::PersistConfig => KeyCode::Char('W'),
+            Action::AgeTint => KeyCode::Char('a'),
+            Action::GlyphStability => KeyCode::Char('g'),
+            Action::Replay => KeyCode::Char('l'),
+            Action::RampNext => KeyCode::Char('m'),
+            Action::BlocksToggle => KeyCode::Char('b'),
+            Action::SafeGlyphs => KeyCode::Char('A'),
+            Action::AlignNext => KeyCode::Char('j'),
+            Action::PaletteNext => KeyCode::Char('c'),
+            Action::GrayView => KeyCode::Char('G'),
+            Action::FallingNext => KeyCode::Char('s'),
+            Action::EffectNext => KeyCode::Tab,
+            Action::Flip => KeyCode::Char('i'),
+            Action::Flare => KeyCode::Char('u'),
+            Action::Relight => KeyCode::Char('r'),
+            Action::WindLeft => KeyCode::Left,
+            Action::WindRight => KeyCode::Right,
+            Action::SelfWeightDown => KeyCode::Char(','),
+            Action::SelfWeightUp => KeyCode::Char('.'),
+            Action::SideDiffusionDown => KeyCode::Char('{'),
+            Action::SideDiffusionUp => KeyCode::Char('}'),
+            Action::IntensityDown => KeyCode::Char('['),
+            Action::IntensityUp => KeyCode::Char(']'),
+            Action::Randomize => KeyCode::Char('R'),
+            Action::ZoomIn => KeyCode::Char('+'),
+            Action::ZoomOut => KeyCode::Char('-'),
+            Action::PanUp => KeyCode::Up,
+            Action::PanDown => KeyCode::Down,
+            Action::HeatView => KeyCode::Char('h'),
+            Action::WindView => KeyCode::Char('v'),
+        }
+    }
+}
+
+/// A key as written in the config, e.g. `"x"`, `"space"`, `"left"` or `"f5"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key(pub KeyCode);
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        let mut chars = spec.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key(KeyCode::Char(c)));
+        }
+
+        let code = match spec.to_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                Some(number @ 1..=12) => KeyCode::F(number),
+                _ => return Err(format!("unknown key '{spec}'")),
+            },
+        };
+        Ok(Key(code))
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        match key.0 {
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(number) => format!("f{number}"),
+            code => format!("{code:?}").to_lowercase(),
+        }
+    }
+}
+
+/// Maps pressed keys to actions, starting from the default bindings.
+pub struct KeyBindings {
+    actions: HashMap<KeyCode, Action>,
+}
+
+impl KeyBindings {
+    /// Binds each action in `overrides` to its key in place of the default.
+    /// A key taken over from another action no longer triggers that action.
+    pub fn new(overrides: &BTreeMap<Action, Key>) -> KeyBindings {
+        let mut actions = HashMap::new();
+        for action in Action::ALL {
+            if !overrides.contains_key(&action) {
+                actions.insert(action.default_key(), action);
+            }
+        }
+        for (&action, key) in overrides {
+            actions.insert(key.0, action);
+        }
+        KeyBindings { actions }
+    }
+
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.actions.get(&code).copied()
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::new(&BTreeMap::new())
+    }
+}
@@ -0,0 +1,20 @@
+//! A classic demoscene fire effect for the terminal.
+//!
+//! [`App`] runs the simulation and [`FireWidget`] draws it into any part of a
+//! ratatui frame, so the fire can be embedded in other terminal apps. Set up
+//! an app with [`App::builder`].
+
+mod app;
+mod builder;
+pub mod config;
+pub mod effect;
+pub mod keys;
+pub mod palette;
+pub mod ramp;
+pub mod shape;
+pub mod sixel;
+mod widget;
+
+pub use app::{App, HEAT_SCALE, Heat, PostProcess, TICK_RATE, step_grid};
+pub use builder::AppBuilder;
+pub use widget::FireWidget;
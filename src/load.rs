@@ -0,0 +1,74 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+/// How often the load is read.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Something that tells how hard the machine is working, from 0 when idle
+/// to 1 when flat out, for `--react-load`.
+pub trait LoadSource: Send + 'static {
+    /// Reads the load now, or `None` if it can't be read this time.
+    fn sample(&mut self) -> Option<f32>;
+}
+
+/// The one-minute load average shared out over the CPUs, so it follows the
+/// machine's work smoothly rather than spiking with every process.
+#[cfg(unix)]
+pub struct LoadAverage {
+    cpus: f32,
+}
+
+#[cfg(unix)]
+impl LoadAverage {
+    pub fn new() -> LoadAverage {
+        let cpus = thread::available_parallelism().map_or(1, usize::from);
+        LoadAverage { cpus: cpus as f32 }
+    }
+}
+
+#[cfg(unix)]
+impl LoadSource for LoadAverage {
+    fn sample(&mut self) -> Option<f32> {
+        let mut loads = [0.0];
+        // SAFETY: the pointer is to a live local with room for the one
+        // sample asked for
+        if unsafe { libc::getloadavg(loads.as_mut_ptr(), 1) } < 1 {
+            return None;
+        }
+        Some((loads[0] as f32 / self.cpus).clamp(0.0, 1.0))
+    }
+}
+
+/// Reads a [`LoadSource`] every second on a thread of its own, so a slow
+/// read never holds up a frame. The thread runs until the program exits.
+pub struct LoadMeter {
+    // The last load read, as the bits of an `f32`
+    level: Arc<AtomicU32>,
+}
+
+impl LoadMeter {
+    pub fn start(mut source: impl LoadSource) -> LoadMeter {
+        let level = Arc::new(AtomicU32::new(0.0f32.to_bits()));
+        let shared = Arc::clone(&level);
+        thread::spawn(move || {
+            loop {
+                if let Some(load) = source.sample() {
+                    shared.store(load.to_bits(), Ordering::Relaxed);
+                }
+                thread::sleep(SAMPLE_INTERVAL);
+            }
+        });
+        LoadMeter { level }
+    }
+
+    /// The last load read, from 0 to 1.
+    pub fn level(&self) -> f32 {
+        f32::from_bits(self.level.load(Ordering::Relaxed))
+    }
+}
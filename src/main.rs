@@ -1,235 +1,2761 @@
+mod ambient;
+mod asciicast;
+#[cfg(feature = "async")]
+mod async_loop;
+mod csv_export;
+mod cycle;
+mod events;
+mod frame_budget;
+#[cfg(feature = "gif")]
+mod gif_export;
+mod load;
+mod mirror;
+mod pixels;
+mod script;
+mod source_image;
+mod timelapse;
+
+use fire_in_the_term::{
+    App, FireWidget, Heat, TICK_RATE,
+    config::{BaseCurve, Falling, FireConfig, UpdateOrder},
+    effect::Effect,
+    keys::{Action, KeyBindings},
+    palette::{self, ColorMode, Colormap, Preset},
+    ramp::{CharRamp, GlyphVariety},
+    shape::FlameShape,
+};
+
+use ambient::AmbientOut;
+use clap::{CommandFactory, Parser, error::ErrorKind};
 use crossterm::{
-    event::{self, Event as CrosstermEvent, KeyCode, KeyEventKind},
-    execute,
+    cursor::{Hide, MoveTo},
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+        Event as CrosstermEvent, KeyCode, KeyEventKind, MouseButton, MouseEventKind,
+    },
+    execute, queue,
     terminal::{
-        EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
-        size as terminal_size,
+        Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
+        enable_raw_mode, size as terminal_size, window_size,
     },
 };
-use rand::{random_bool, random_range};
+use cycle::Cycle;
+use events::EventLog;
+use frame_budget::FrameBudget;
+use load::LoadMeter;
+use mirror::Mirrors;
+use rand::random_range;
 use ratatui::{
     backend::CrosstermBackend,
     prelude::*,
     widgets::{Block, Borders, Paragraph},
 };
+use script::{Cue, Script};
 use std::{
+    env,
     error::Error,
-    io,
-    time::{Duration, Instant},
+    fmt, fs,
+    io::{self, IsTerminal, Write},
+    mem,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use timelapse::Timelapse;
 
-struct App {
-    fire_grid: Vec<Vec<u8>>,
-    width: usize,
-    height: usize,
-    char_map: Vec<Vec<char>>,
-    color_map: Vec<Color>,
-}
-
-impl App {
-    fn new(width: usize, height: usize) -> App {
-        let char_map = vec![
-            vec![' '],
-            vec!['.', '\'', '`', ','],
-            vec!['~', '-', ';', ':'],
-            vec!['"', ';', ':', '^'],
-            vec!['!', '?', '=', '"'],
-            vec!['(', ')', '|', '!'],
-            vec!['[', ']', '\\', '/'],
-            vec!['{', '}', 'I', 'V'],
-            vec!['o', 'T', 'O', 'V'],
-            vec!['H', 'A', '0', '*'],
-            vec!['M', 'W', '%', 'X'],
-            vec!['#', '$', '@', '&'],
-        ];
-
-        let color_map = vec![
-            Color::Black,              // For very low/no heat (background)
-            Color::Rgb(175, 0, 0),     // Deep red, subtle embers
-            Color::Rgb(255, 0, 0),     // Red
-            Color::Rgb(255, 150, 50),  // Orange-Red
-            Color::Rgb(255, 175, 75),  // Dark Orange
-            Color::Rgb(255, 200, 100), // Orange
-            Color::Yellow,             // Yellow
-            Color::Rgb(255, 255, 150), // Light Yellow
-            Color::White,              // White, very hot core
-            Color::Rgb(255, 255, 200), // Brighter white
-            Color::Rgb(255, 255, 250), // Almost pure white for brightest parts
-        ];
-
-        App {
-            fire_grid: vec![vec![0; width]; height],
-            width,
-            height,
-            char_map,
-            color_map,
-        }
-    }
-
-    fn resize(&mut self, new_width: usize, new_height: usize) {
-        if self.width != new_width || self.height != new_height {
-            self.width = new_width;
-            self.height = new_height;
-            self.fire_grid = vec![vec![0; self.width]; self.height];
-        }
-    }
-
-    /// Updates the fire grid for the next animation frame.
-    /// This simulates heat decay, diffusion, and new heat injection.
-    fn update_fire(&mut self) {
-        // Create a buffer for the next state of the grid to avoid modifying
-        // the current grid while calculating new values based on its current state.
-        let mut next_grid = vec![vec![0; self.width]; self.height];
-
-        // Step 1: Heat propagation (upwards diffusion) and decay
-        // Iterate from the second-to-last row up to the first row (top)
-        // This simulates heat rising from below.
-        for y in (0..self.height - 1).rev() {
-            // Start from y = height - 2 (second to last row)
-            for x in 0..self.width {
-                let current_heat = self.fire_grid[y][x];
-                let below_heat = self.fire_grid[y + 1][x];
-
-                // Base heat from the cell directly below, but heavily reduced to limit upward movement.
-                // Combined with a portion of the current cell's heat to create a more "flickering in place" effect.
-                let mut new_cell_heat = (below_heat / 2).saturating_add(current_heat / 3);
-
-                // Add small contributions from side neighbors (diffusion)
-                if x > 0 {
-                    new_cell_heat = new_cell_heat.saturating_add(self.fire_grid[y][x - 1] / 8);
-                }
-                if x < self.width - 1 {
-                    new_cell_heat = new_cell_heat.saturating_add(self.fire_grid[y][x + 1] / 8);
-                }
+/// Light your terminal on fire
+#[derive(Parser)]
+#[command(name = "fitt", version, about)]
+struct Cli {
+    /// Load settings from a TOML config file. Flags override its values.
+    /// Defaults to `~/.config/fitt/config.toml` when that exists
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
 
-                // Apply decay: Higher decay to keep the flame localized
-                let decay_amount = random_range(15..=18);
-                let decayed_heat = new_cell_heat.saturating_sub(decay_amount);
+    /// Config file that `W` saves the current settings to. Defaults to the
+    /// `--config` file, or else `~/.config/fitt/config.toml`
+    #[arg(long, value_name = "PATH")]
+    persist_config: Option<PathBuf>,
 
-                // Add random fluctuation for flickering. More intense fluctuation.
-                let fluctuation = random_range(12..=15);
-                next_grid[y][x] = if random_bool(0.5) {
-                    decayed_heat.saturating_add(fluctuation)
-                } else {
-                    decayed_heat.saturating_sub(fluctuation)
-                };
-            }
+    /// Print the effective config as TOML to stderr after exiting
+    #[arg(long)]
+    print_config_on_exit: bool,
+
+    /// Print the config that the config file and flags add up to as TOML, and
+    /// exit without starting the fire
+    #[arg(long)]
+    show_config: bool,
+
+    /// Check that a config file loads, printing `OK` or what is wrong with
+    /// it, and exit without starting the fire
+    #[arg(long, value_name = "PATH")]
+    check_config: Option<PathBuf>,
+
+    /// Check that a `--palette-file` loads, printing `OK` or what is wrong
+    /// with it, and exit without starting the fire
+    #[arg(long, value_name = "PATH")]
+    check_palette: Option<PathBuf>,
+
+    /// Write the palette the config file and flags add up to as `#RRGGBB`
+    /// lines, ready for `--palette-file`, and exit without starting the fire
+    #[arg(long, value_name = "PATH")]
+    export_palette: Option<PathBuf>,
+
+    /// Print the palette as a gradient bar in each `--colors` mode and in
+    /// gray, to compare them in this terminal, and exit without starting
+    /// the fire
+    #[arg(long)]
+    preview_colors: bool,
+
+    /// Load the flame colors from a GIMP `.gpl` file or a list of `#RRGGBB` lines
+    #[arg(long, value_name = "PATH")]
+    palette_file: Option<PathBuf>,
+
+    /// Use a three-color red, yellow and white palette with block glyphs, for
+    /// an 80s terminal look
+    #[arg(long, conflicts_with = "palette_file")]
+    retro: bool,
+
+    /// Recolor a single palette entry, counting from 0 for the coldest, e.g.
+    /// `--band 6=#FF00FF`. Can be repeated
+    #[arg(long = "band", value_name = "INDEX=COLOR", value_parser = parse_band)]
+    bands: Vec<(usize, Color)>,
+
+    /// Switch palettes instantly with `c` instead of cross-fading
+    #[arg(long)]
+    instant_palette: bool,
+
+    /// Color the flame like a glowing blackbody, from `--blackbody-min` Kelvin
+    /// for the coldest heat to `--blackbody-max` for the hottest
+    #[arg(long, conflicts_with_all = ["palette_file", "retro"])]
+    blackbody: bool,
+
+    /// Temperature of the coldest heat with `--blackbody`
+    #[arg(long, value_name = "KELVIN", default_value_t = 800.0)]
+    blackbody_min: f32,
+
+    /// Temperature of the hottest heat with `--blackbody`
+    #[arg(long, value_name = "KELVIN", default_value_t = 6500.0)]
+    blackbody_max: f32,
+
+    /// Build the palette from two colors, e.g. `--gradient #330000 #FFFF99`,
+    /// blending from the first for the coldest heat to the second for the
+    /// hottest
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["FROM", "TO"],
+        conflicts_with_all = ["palette_file", "retro", "blackbody"]
+    )]
+    gradient: Option<Vec<Color>>,
+
+    /// Color the flame with a matplotlib colormap. `inferno` and `magma` start
+    /// from black like a fire does
+    #[arg(
+        long,
+        value_enum,
+        value_name = "COLORMAP",
+        conflicts_with_all = ["palette_file", "retro", "blackbody", "gradient"]
+    )]
+    colormap: Option<Colormap>,
+
+    /// Color the flame with one of the built-in palettes
+    #[arg(
+        long,
+        value_enum,
+        value_name = "PALETTE",
+        conflicts_with_all = [
+            "palette_file",
+            "retro",
+            "blackbody",
+            "gradient",
+            "colormap",
+            "auto_palette_time"
+        ]
+    )]
+    palette: Option<Preset>,
+
+    /// Colors in a `--gradient` or `--colormap` palette
+    #[arg(
+        long,
+        value_name = "COUNT",
+        default_value_t = 16,
+        value_parser = clap::value_parser!(u16).range(2..=256)
+    )]
+    gradient_bands: u16,
+
+    /// Follow the time of day with the palette: warm fire colors in the
+    /// evening and at night, cool bright ones around midday. Changing the
+    /// palette with `c` or `R` stops following it
+    #[arg(
+        long,
+        conflicts_with_all = ["palette_file", "retro", "blackbody", "gradient", "colormap"]
+    )]
+    auto_palette_time: bool,
+
+    /// Print a single plain-text frame to stdout and exit. Implied when stdout
+    /// is not a terminal
+    #[arg(long)]
+    once: bool,
+
+    /// Color the `--once` frame with ANSI escape codes even when stdout is not
+    /// a terminal, e.g. to convert it to HTML
+    #[arg(long)]
+    ansi: bool,
+
+    /// Record the flame to an animated GIF at this path instead of showing it
+    #[cfg(feature = "gif")]
+    #[arg(long, value_name = "PATH")]
+    gif: Option<PathBuf>,
+
+    /// Write the raw heat grid of every frame to a CSV file at this path
+    /// instead of showing the flame, for analysis in other tools
+    #[arg(long, value_name = "PATH")]
+    dump_csv: Option<PathBuf>,
+
+    /// Start from the heat grid in a CSV file, such as a frame of a
+    /// `--dump-csv` file, instead of a cold one. A grid of another size is
+    /// kept at the bottom in the middle, cut off or padded
+    #[arg(long, value_name = "PATH")]
+    load_grid: Option<PathBuf>,
+
+    /// Record the flame to an asciinema v2 `.cast` file at this path instead
+    /// of showing it, ready to play back or upload to asciinema.org
+    #[arg(long, value_name = "PATH")]
+    asciicast: Option<PathBuf>,
+
+    /// Number of frames to record with `--gif`, `--dump-csv` or `--asciicast`
+    #[arg(long, value_name = "N", default_value_t = 100)]
+    frames: usize,
+
+    /// Colors the terminal can show. Detected from `COLORTERM` and `TERM` by
+    /// default
+    #[arg(long, value_enum, value_name = "MODE")]
+    colors: Option<ColorMode>,
+
+    /// Keep the output to the terminal to about this many bytes a frame, for
+    /// slow links. Busy frames are drawn with fewer colors or skipped
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    max_bytes_per_frame: Option<u32>,
+
+    /// Draw the flame without color, in the terminal's foreground, for
+    /// monochrome screens
+    #[arg(long)]
+    mono: bool,
+
+    /// Dither between neighboring palette colors to smooth out the bands of
+    /// small palettes such as `--retro`
+    #[arg(long)]
+    dither: bool,
+
+    /// Multiply every drawn color's channels by this, e.g. 0.8 for a dimmer
+    /// fire. 1 leaves the colors as they are
+    #[arg(long, value_name = "FACTOR")]
+    brightness: Option<f32>,
+
+    /// Push drawn colors away from mid gray by this factor, or pull them
+    /// towards it below 1. 1 leaves the colors as they are
+    #[arg(long, value_name = "FACTOR")]
+    contrast: Option<f32>,
+
+    /// Darken the colors towards the corners of the frame, from 0 for not at
+    /// all to 1 for black corners
+    #[arg(long, value_name = "STRENGTH")]
+    vignette: Option<f32>,
+
+    /// Color the hot base of the flame blue and the rest with the palette,
+    /// like a gas flame with yellow tips
+    #[arg(long)]
+    gas_flame: bool,
+
+    /// Fraction of the flame height, from the bottom, that `--gas-flame`
+    /// colors blue
+    #[arg(long, value_name = "FRACTION")]
+    gas_transition: Option<f32>,
+
+    /// Glyphs used to draw the flame. Cycle at runtime with `m`
+    #[arg(long, value_enum, value_name = "RAMP")]
+    char_ramp: Option<CharRamp>,
+
+    /// Resample the glyph ramp to the palette's color count, so glyphs and
+    /// colors change at the same heats
+    #[arg(long)]
+    align_bands: bool,
+
+    /// Confine the flame to a silhouette, e.g. `triangle` for a torch
+    #[arg(long, value_enum, value_name = "SHAPE")]
+    shape: Option<FlameShape>,
+
+    /// Rearrange the fire, e.g. `twin` for two flames that lean into each
+    /// other and merge, or `fireball` for a round ball of fire
+    #[arg(long, value_enum, value_name = "EFFECT")]
+    effect: Option<Effect>,
+
+    /// Order the rows are worked out in each frame, to experiment with. Only
+    /// changes which random numbers fall on which cell, since every row is
+    /// worked out from the last frame
+    #[arg(long, value_enum, value_name = "ORDER")]
+    update_order: Option<UpdateOrder>,
+
+    /// Work out each cell's random numbers from its position and the seed
+    /// instead of one stream, so a seed gives every cell the same ones
+    /// whatever the terminal's size
+    #[arg(long)]
+    cell_seeding: bool,
+
+    /// Pick glyphs from each cell's heat instead of at random every frame, so
+    /// characters only change when the heat does. Toggle at runtime with `g`
+    #[arg(long)]
+    glyph_stability: bool,
+
+    /// How glyphs are otherwise picked: `random` for every cell, `hashed`
+    /// from each cell's position with one random number a frame, or `fixed`
+    /// for the same glyph per band
+    #[arg(long, value_enum, value_name = "VARIETY")]
+    glyph_variety: Option<GlyphVariety>,
+
+    /// Only pick glyphs at random for cells at least this hot, from 0 to 255.
+    /// Cooler cells keep the glyph that follows their heat, so the flame's
+    /// tips stay calm while the base shimmers
+    #[arg(long, value_name = "HEAT")]
+    shimmer_threshold: Option<u8>,
+
+    /// Only redraw cells whose glyph band or color changed since the last
+    /// frame, greatly cutting the output over slow connections
+    #[arg(long)]
+    diff_render: bool,
+
+    /// Let this fraction of each frame linger into the next, for a dreamy
+    /// motion blur. 0 disables it
+    #[arg(long, value_name = "FADE")]
+    blur_trail: Option<f32>,
+
+    /// Let the hottest cells glow, lighting a halo around them with this
+    /// strength, e.g. 0.5. 0 disables it
+    #[arg(long, value_name = "STRENGTH")]
+    bloom: Option<f32>,
+
+    /// Hold back this fraction of each new frame for a slow, molten flame. 0
+    /// keeps the flame snappy and 1 freezes it
+    #[arg(long, value_name = "FRACTION")]
+    viscosity: Option<f32>,
+
+    /// Simulate each tick in this many smaller steps, for smoother motion at
+    /// the same frame rate. From 1 to 16
+    #[arg(long, value_name = "N")]
+    substeps: Option<u32>,
+
+    /// Fade the flame out over this many columns towards the left and right
+    /// edges instead of cutting it off there
+    #[arg(long, value_name = "COLUMNS")]
+    edge_falloff: Option<usize>,
+
+    /// Keep the flame within this fraction of the screen height, from 0 to 1,
+    /// leaving the space above it clear
+    #[arg(long, value_name = "FRACTION")]
+    max_height: Option<f32>,
+
+    /// Make upper rows cool faster for tapered flame tips. 0 keeps decay uniform
+    #[arg(long, value_name = "FACTOR")]
+    tip_decay: Option<f32>,
+
+    /// How fierce the flame burns, from 0 to 1. Higher values cool it slower
+    /// so it reaches higher. 0.5 is the usual fire
+    #[arg(long, value_name = "INTENSITY")]
+    intensity: Option<f32>,
+
+    /// Spread heat sideways from this many cells to either side instead of
+    /// just the nearest ones, for wider, softer flames. At most 16
+    #[arg(long, value_name = "CELLS")]
+    diffusion_radius: Option<usize>,
+
+    /// How the chance of the base catching falls off from the middle to the
+    /// edges: power burns almost out to them, gaussian in a narrow mound
+    #[arg(long, value_enum, value_name = "CURVE")]
+    base_curve: Option<BaseCurve>,
+
+    /// Exponent of the power base curve. Smaller values widen the base
+    #[arg(long, value_name = "EXPONENT")]
+    base_exponent: Option<f32>,
+
+    /// Simulate a chimney draft pulling air in at the sides, which narrows
+    /// the base into a tight column over time. Takes a strength from 0 to 1,
+    /// 1 if left out
+    #[arg(long, value_name = "STRENGTH", num_args = 0..=1, default_missing_value = "1")]
+    draft: Option<f32>,
+
+    /// Shoot a jet of flame up from a random spot of the base about this many
+    /// times a minute, for a livelier fire. 0 disables it
+    #[arg(long, value_name = "PER_MINUTE")]
+    flare_rate: Option<f32>,
+
+    /// Heat the bottom row up over this many hits instead of igniting cells
+    /// at full heat, for a smoother, steadier base
+    #[arg(long, value_name = "FRAMES")]
+    ignition_build: Option<u32>,
+
+    /// Light the base of the fire according to how bright each column of a
+    /// binary PPM or PGM image is, so the flame follows its outline, e.g. a
+    /// city skyline
+    #[arg(long, value_name = "PATH")]
+    source_image: Option<PathBuf>,
+
+    /// Only let the fire burn where a text file marks it, with any character
+    /// but a space, e.g. letters drawn in `#` to set a word alight
+    #[arg(long, value_name = "PATH")]
+    burn_mask: Option<PathBuf>,
+
+    /// Cool the flame unevenly in drifting patches, taking up to this much extra
+    /// heat from each cell, for a wispier texture. 0 keeps the decay even
+    #[arg(long, value_name = "HEAT")]
+    cooling_noise: Option<f32>,
+
+    /// Flicker with smooth noise drifting over the flame, so nearby cells
+    /// flicker together like a candle instead of at random
+    #[arg(long)]
+    coherent_flicker: bool,
+
+    /// Width in cells of the patches that flicker together with
+    /// --coherent-flicker
+    #[arg(long, value_name = "CELLS")]
+    flicker_scale: Option<f32>,
+
+    /// How fast the --coherent-flicker patches change, in patches per frame.
+    /// 0 holds them still
+    #[arg(long, value_name = "SPEED")]
+    flicker_speed: Option<f32>,
+
+    /// Make the flame swell and fade like breathing at this many beats per
+    /// minute. 0 disables it
+    #[arg(long, value_name = "BPM")]
+    pulse: Option<f32>,
+
+    /// Let the base of the flame wander from side to side and back this many
+    /// times a minute, so it burns hottest in a different spot. 0 keeps it in
+    /// the middle
+    #[arg(long, value_name = "PER_MINUTE")]
+    wander: Option<f32>,
+
+    /// Minimum heat of every cell, for a guaranteed glow
+    #[arg(long, value_name = "HEAT")]
+    floor_heat: Option<u8>,
+
+    /// Maximum heat of every cell, to prevent full saturation
+    #[arg(long, value_name = "HEAT")]
+    ceil_heat: Option<u8>,
+
+    /// Cool the fire harder whenever it has been burning near white all over
+    /// for a few seconds, until it recovers, so a bad combination of settings
+    /// can't blow it out for good. A blown out fire is always pointed out
+    #[arg(long)]
+    auto_dampen: bool,
+
+    /// Simulate on a fixed grid and smoothly scale it up to the screen, keeping
+    /// the CPU cost constant regardless of terminal size
+    #[arg(long, value_name = "WxH", value_parser = parse_size)]
+    sim_size: Option<(usize, usize)>,
+
+    /// Simulate flames wider than this many columns at half resolution to
+    /// keep frames quick on ultrawide terminals. Defaults to 400, and 0 turns
+    /// this off
+    #[arg(long, value_name = "COLUMNS")]
+    ultrawide_columns: Option<usize>,
+
+    /// Tint freshly ignited cells whiter than long-lived ones. Toggle at
+    /// runtime with `a`
+    #[arg(long)]
+    age_tint: bool,
+
+    /// Leave dark ash for a moment where a hot cell has just gone out, before
+    /// it turns black. Takes an optional color, dark gray by default
+    #[arg(
+        long,
+        value_name = "COLOR",
+        num_args = 0..=1,
+        default_missing_value = "#404040"
+    )]
+    ash: Option<Color>,
+
+    /// Let snow or ash fall over the flame, melting or burning up where it
+    /// meets the heat
+    #[arg(long, value_enum, value_name = "KIND")]
+    falling: Option<Falling>,
+
+    /// Flip the palette so hot cells are dark and cold ones bright, for a
+    /// shadow flame on a light background
+    #[arg(long)]
+    invert: bool,
+
+    /// Background color behind the flame, e.g. `white` or `#FFEEDD`
+    #[arg(long, value_name = "COLOR")]
+    background: Option<Color>,
+
+    /// Leave the coldest band and the border in the terminal's default
+    /// colors, so the fire sits over the terminal's own background
+    #[arg(long, conflicts_with = "background")]
+    transparent: bool,
+
+    /// Steady wind blowing the flame sideways, in cells per row. Negative
+    /// values blow left
+    #[arg(long, value_name = "CELLS", allow_negative_numbers = true)]
+    wind: Option<f32>,
+
+    /// Make the wind sway the flame back and forth on its own
+    #[arg(long)]
+    auto_wind: bool,
+
+    /// How far `--auto-wind` swings to either side, in cells per row
+    #[arg(long, value_name = "CELLS")]
+    wind_amplitude: Option<f32>,
+
+    /// Frames `--auto-wind` takes to swing back and forth once
+    #[arg(long, value_name = "FRAMES")]
+    wind_period: Option<f32>,
+
+    /// Shift the rising heat sideways by up to this many cells in small,
+    /// wandering gusts, for wavering, licking flames. 0 keeps the rise smooth
+    #[arg(long, value_name = "CELLS")]
+    turbulence: Option<f32>,
+
+    /// Draw every cell two characters wide, for a broader flame on terminals
+    /// with narrow fonts
+    #[arg(long)]
+    wide: bool,
+
+    /// Draw the flame in braille dots, 2 across and 4 down in every character,
+    /// for much finer detail
+    #[arg(long)]
+    braille: bool,
+
+    /// Burn sideways, from the left edge towards the right, by turning the
+    /// drawn frame a quarter turn, for a tall, narrow sidebar
+    #[arg(long)]
+    rotate: bool,
+
+    /// Join the left and right edges, so heat spreading or blown off one side
+    /// comes back in at the other
+    #[arg(long)]
+    wrap: bool,
+
+    /// Draw a rippling reflection of the flame in this many rows below it
+    #[arg(long, value_name = "ROWS")]
+    reflect: Option<usize>,
+
+    /// Burn this many fires one behind the other, up to 4, each further one
+    /// dimmer and slower, for a sense of depth
+    #[arg(long, value_name = "N")]
+    layers: Option<usize>,
+
+    /// Seed for the simulation's randomness. Without it a random seed is used
+    /// and printed on exit so the run can be reproduced
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Seed for picking the glyphs, so a `--seed` run also draws the same
+    /// characters. Without it the glyphs keep shimmering differently each run
+    #[arg(long, value_name = "SEED")]
+    glyph_seed: Option<u64>,
+
+    /// Split the screen into this many side-by-side fires, each with its own
+    /// palette and glyphs
+    #[arg(long, value_name = "PANES", value_parser = clap::value_parser!(u8).range(1..=8))]
+    split: Option<u8>,
+
+    /// Sides of each pane to draw a border on, as a comma-separated list of
+    /// `top`, `bottom`, `left` and `right`, or `all` or `none`, so the fire
+    /// can sit flush against the screen edge
+    #[arg(long, value_name = "SIDES", value_parser = parse_borders, default_value = "all")]
+    border: Borders,
+
+    /// Where to put the fire when it is narrower than its pane: `left`,
+    /// `center` or `right`
+    #[arg(long, value_name = "SIDE", value_parser = parse_alignment, default_value = "center")]
+    align: Alignment,
+
+    /// Keep the average CPU use under this percentage of a core by slowing the
+    /// animation down when frames get expensive
+    #[arg(long, value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100))]
+    max_cpu: Option<u8>,
+
+    /// Animate at this many frames per second, simulating the flame at a
+    /// lower resolution while the terminal is too large to keep up
+    #[arg(
+        long,
+        value_name = "FPS",
+        value_parser = clap::value_parser!(u16).range(1..=240),
+        conflicts_with_all = ["max_cpu", "sim_size"]
+    )]
+    target_fps: Option<u16>,
+
+    /// Animate at this many frames per second instead of about 16. The flame
+    /// burns at the same pace, in smaller or larger steps
+    #[arg(
+        long,
+        value_name = "FPS",
+        value_parser = clap::value_parser!(u16).range(1..=240),
+        conflicts_with_all = ["max_cpu", "target_fps"]
+    )]
+    fps: Option<u16>,
+
+    /// Save a numbered PPM image of the flame every SECONDS seconds into the
+    /// `--out` directory, for a time-lapse
+    #[arg(long, value_name = "SECONDS", value_parser = parse_interval, requires = "out")]
+    timelapse: Option<Duration>,
+
+    /// Directory the `--timelapse` images are saved in. Created if missing
+    #[arg(long, value_name = "DIR", requires = "timelapse")]
+    out: Option<PathBuf>,
+
+    /// Also show the fire on these terminals, given as comma-separated tty
+    /// devices such as `/dev/pts/1,/dev/pts/2`. Every terminal shows the same
+    /// frame, cut down to the size they all fit
+    #[arg(
+        long,
+        value_name = "TTYS",
+        value_delimiter = ',',
+        conflicts_with_all = ["sixel", "bar", "bottom_strip"]
+    )]
+    output: Vec<PathBuf>,
+
+    /// Write a JSON line to PATH for every key that changes the fire and every
+    /// resize, with the new value and a timestamp, e.g. for a dashboard. Use
+    /// `/dev/fd/3` to write to an open file descriptor
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["bar", "bottom_strip"])]
+    events_json: Option<PathBuf>,
+
+    /// Write a color standing for the whole flame to PATH every frame, as a
+    /// `#RRGGBB` line, e.g. for a script driving smart lights. Use
+    /// `/dev/fd/3` to write to an open file descriptor
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["bar", "bottom_strip"])]
+    ambient_out: Option<PathBuf>,
+
+    /// Make changes at set times from a file with one per line, as
+    /// `<seconds> <action>` or `<seconds> <setting> <value>`, e.g.
+    /// `5 palette_next` or `10 wind 0.5`
+    #[arg(long, value_name = "PATH")]
+    script: Option<PathBuf>,
+
+    /// Burn for a while, go out, stay dark for a moment and light again, in
+    /// a loop, e.g. `burn=30s,dark=5s`. The dark time starts once the flame
+    /// has died down
+    #[arg(long, value_name = "burn=SECONDS,dark=SECONDS", value_parser = parse_cycle)]
+    cycle: Option<(Duration, Duration)>,
+
+    /// Let the fire burn hotter the harder the machine works, following the
+    /// load average over the CPUs
+    #[cfg(unix)]
+    #[arg(long)]
+    react_load: bool,
+
+    /// Keep the last FRAMES simulated frames so `l` can loop them as a frozen
+    /// animation
+    #[arg(long, value_name = "FRAMES", default_value_t = 0)]
+    history: usize,
+
+    /// Cap the memory `--history` may take to this many megabytes, keeping
+    /// fewer frames on large terminals. Split panes share the budget
+    #[arg(long, value_name = "MB")]
+    max_memory: Option<usize>,
+
+    /// Vary each frame interval randomly by up to this many milliseconds, for
+    /// less mechanically periodic motion
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    tick_jitter: u64,
+
+    /// Draw the flame as sixel graphics instead of characters. Needs a
+    /// terminal with sixel support
+    #[arg(long, conflicts_with = "split")]
+    sixel: bool,
+
+    /// On quitting, let the flame die down to a bed of glowing embers that
+    /// fades out over a few seconds. Any key skips it
+    #[arg(long, conflicts_with = "sixel")]
+    ember_outro: bool,
+
+    /// Keep the fire in this many rows at the bottom of the normal screen and
+    /// leave the rest to the shell. Run it in the background (`fitt
+    /// --bottom-strip 5 &`) for a live wallpaper, and stop it with `kill`
+    #[arg(long, value_name = "ROWS", conflicts_with_all = ["sixel", "split"])]
+    bottom_strip: Option<u16>,
+
+    /// Print a single row of fire this many columns wide and exit, for status
+    /// bars such as polybar or tmux
+    #[arg(
+        long,
+        value_name = "WIDTH",
+        value_parser = clap::value_parser!(u16).range(1..),
+        conflicts_with_all = ["once", "sixel", "split", "bottom_strip"]
+    )]
+    bar: Option<u16>,
+
+    /// Keep printing a fresh `--bar` on a new line every frame instead of
+    /// exiting, for status bars that follow a command's output
+    #[arg(long, requires = "bar")]
+    bar_follow: bool,
+
+    /// Start with the animation paused, e.g. to set up a recording. Press
+    /// space to start it
+    #[arg(long, conflicts_with_all = ["bottom_strip", "bar"])]
+    start_paused: bool,
+
+    /// For the first SECONDS seconds, ignore a single press of the quit key
+    /// and only quit when it is pressed twice in quick succession, to guard
+    /// against accidental exits. Ctrl-C still quits at once
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        value_parser = parse_interval,
+        conflicts_with_all = ["bottom_strip", "bar"]
+    )]
+    lock: Option<Duration>,
+
+    /// Simulate this many frames before the first one is shown, so the flame
+    /// starts fully grown. Defaults to 0, or 100 for `--once`, `--gif`,
+    /// `--dump-csv` and `--asciicast`
+    #[arg(long, value_name = "FRAMES")]
+    warmup: Option<usize>,
+
+    /// Pause the animation while the terminal window is unfocused to save CPU
+    #[arg(long)]
+    pause_unfocused: bool,
+
+    /// Capture the mouse, so the scroll wheel raises and lowers the
+    /// intensity like `]` and `[`, and clicking or dragging lights the fire
+    /// under the pointer. Ctrl+scroll sizes the patch that is lit
+    #[arg(long)]
+    mouse: bool,
+
+    /// Show a debug overlay and enable debugging keys: `h` toggles the raw heat
+    /// view and `v` the wind view
+    #[arg(long)]
+    debug: bool,
+}
+
+/// Parses a `WxH` size such as `40x30`.
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    let (width, height) = value
+        .split_once('x')
+        .ok_or_else(|| "expected WxH, e.g. 40x30".to_string())?;
+    let parse = |n: &str| match n.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("'{n}' is not a positive number")),
+    };
+    Ok((parse(width)?, parse(height)?))
+}
+
+/// Parses an `INDEX=COLOR` palette override such as `6=#FF00FF`.
+fn parse_band(value: &str) -> Result<(usize, Color), String> {
+    let (index, color) = value
+        .split_once('=')
+        .ok_or_else(|| "expected INDEX=COLOR, e.g. 6=#FF00FF".to_string())?;
+    let index = (index.trim().parse()).map_err(|_| format!("'{index}' is not a palette index"))?;
+    let color = (color.trim().parse()).map_err(|_| format!("'{color}' is not a color"))?;
+    Ok((index, color))
+}
+
+fn parse_borders(value: &str) -> Result<Borders, String> {
+    value.split(',').try_fold(Borders::NONE, |borders, side| {
+        let side = match side.trim() {
+            "top" => Borders::TOP,
+            "bottom" => Borders::BOTTOM,
+            "left" => Borders::LEFT,
+            "right" => Borders::RIGHT,
+            "all" => Borders::ALL,
+            "none" => Borders::NONE,
+            side => return Err(format!("'{side}' is not a side")),
+        };
+        Ok(borders | side)
+    })
+}
+
+fn parse_cycle(value: &str) -> Result<(Duration, Duration), String> {
+    let (mut burn, mut dark) = (None, None);
+    for part in value.split(',') {
+        let (name, seconds) = (part.split_once('=')).ok_or_else(|| {
+            format!("expected 'burn=<seconds>' or 'dark=<seconds>', found '{part}'")
+        })?;
+        let seconds = parse_interval(seconds.strip_suffix('s').unwrap_or(seconds))?;
+        match name.trim() {
+            "burn" => burn = Some(seconds),
+            "dark" => dark = Some(seconds),
+            name => return Err(format!("unknown phase '{name}', expected 'burn' or 'dark'")),
         }
+    }
+    match (burn, dark) {
+        (Some(burn), Some(dark)) => Ok((burn, dark)),
+        _ => Err("both 'burn' and 'dark' need a time, e.g. 'burn=30s,dark=5s'".into()),
+    }
+}
 
-        // Step 2: Inject new heat at the bottom (logs/fire source)
-        // This is where new flames are "born"
-        let log_row = self.height - 1; // The very bottom row
-        for x in 0..self.width {
-            // Introduce new random heat. Make it more likely in the center to shape the flame.
-            let distance_from_center = (x as f32 - self.width as f32 / 2.0).abs();
-            let center_bias = 1.0 - (distance_from_center / (self.width as f32 / 2.0)); // 1.0 at center, 0.0 at edges
+fn parse_alignment(value: &str) -> Result<Alignment, String> {
+    match value {
+        "left" => Ok(Alignment::Left),
+        "center" => Ok(Alignment::Center),
+        "right" => Ok(Alignment::Right),
+        side => Err(format!("'{side}' is not left, center or right")),
+    }
+}
 
-            if random_bool(center_bias.powf(0.2) as f64) {
-                // Use higher power for even sharper center concentration
-                // Add significant heat if biased and random chance hits
-                next_grid[log_row][x] = random_range(200..=255);
-            } else {
-                // Ensure some heat decays completely at the bottom if not reignited
-                next_grid[log_row][x] = next_grid[log_row][x].saturating_sub(random_range(5..=10));
-            }
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    match value.parse() {
+        Ok(seconds) if seconds > 0.0 => Duration::try_from_secs_f32(seconds)
+            .map_err(|_| format!("'{value}' is too long an interval")),
+        _ => Err(format!("'{value}' is not a positive number of seconds")),
+    }
+}
+
+/// Ticks to simulate by default before printing a `--once` frame or recording
+/// a GIF, so the flame has time to build up from the empty grid.
+const ONCE_WARMUP_TICKS: usize = 100;
+
+/// Rows simulated for `--bar`, and the row shown, counted from the top. The
+/// bar is a cross-section just above the embers, where the flame flickers most.
+const BAR_HEIGHT: u16 = 8;
+const BAR_ROW: usize = 5;
+
+/// Momentum of the gust from an arrow key press, in cells per row per frame.
+const GUST_IMPULSE: f32 = 0.2;
+
+/// Self-weight change per `,` or `.` press.
+const SELF_WEIGHT_STEP: f32 = 0.05;
+
+/// Radius of the patch a click lights with `--mouse` at first, and the
+/// largest Ctrl+scroll takes it to, in columns.
+const BRUSH_RADIUS: usize = 2;
+const MAX_BRUSH_RADIUS: usize = 12;
+
+/// Intensity change per `[` or `]` press, or per notch of the scroll wheel
+/// with `--mouse`.
+const INTENSITY_STEP: f32 = 0.05;
+
+/// Side diffusion change per `{` or `}` press. The flame is sensitive to
+/// it, so the steps are small.
+const SIDE_DIFFUSION_STEP: f32 = 0.005;
+
+/// Pixels per terminal cell for sixel graphics when the terminal doesn't say.
+const SIXEL_FALLBACK_CELL: (usize, usize) = (10, 20);
+
+/// Lowest resolution `--target-fps` goes down to, as a fraction of the screen
+/// in each direction.
+const MAX_RESOLUTION_DIVISOR: usize = 8;
+/// How long `--target-fps` sticks with a resolution before judging it.
+const RESOLUTION_SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// How soon a second press of the quit key has to follow the first to quit
+/// under `--lock`.
+const QUIT_CONFIRM_WINDOW: Duration = Duration::from_millis(500);
+
+/// How long `--ember-outro` takes to fade the embers out.
+const EMBER_OUTRO: Duration = Duration::from_secs(4);
+
+/// Columns and rows between the arrows of the debug wind view.
+const WIND_VIEW_COLUMNS: u16 = 6;
+const WIND_VIEW_ROWS: u16 = 3;
+
+/// How often a paused loop checks whether a signal asked it to quit.
+const SIGNAL_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often `--auto-palette-time` checks the clock. The palette moves so
+/// little in a minute that each cross-fade is barely visible.
+const AUTO_PALETTE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Event loop state that lives outside the simulation.
+struct Session {
+    debug: bool,
+    // Sides of each pane with a border
+    borders: Borders,
+    // Config captured by the last `w` press
+    saved_config: Option<FireConfig>,
+    // Where `W` saves the config
+    persist_path: Option<PathBuf>,
+    pause_unfocused: bool,
+    unfocused: bool,
+    // Toggled by the pause key
+    paused_by_user: bool,
+    // Toggled by the freeze key, which holds the heat but keeps redrawing
+    frozen: bool,
+    // Toggled by the hard freeze key, which stops everything, drawing
+    // included, so the frame on screen stays exactly as it is
+    hard_frozen: bool,
+    // Whether the hard frozen frame still has to be drawn, e.g. after a resize
+    redraw_frozen: bool,
+    // Whether any fire was blown out at the end of the last frame
+    blown_out: bool,
+    quit: bool,
+    // Until then the quit key only quits when pressed twice, from `--lock`
+    lock_until: Option<Instant>,
+    // When the quit key was last pressed while locked
+    last_quit_press: Option<Instant>,
+    // Set from a signal handler when the process is asked to terminate
+    terminated: Arc<AtomicBool>,
+    bindings: KeyBindings,
+    tick_rate: Duration,
+    // Each frame interval lands randomly within `tick_rate ± tick_jitter`
+    tick_jitter: Duration,
+    // Fraction of a core each frame may use, from `--max-cpu`
+    cpu_budget: Option<f32>,
+    // Moving average of the time spent simulating and drawing a frame
+    average_frame_cost: Duration,
+    // Moving average of the time from one frame to the next
+    average_frame_interval: Duration,
+    // Set when the animation is paused or resumed, so the gap isn't taken
+    // for a slow frame
+    pause_changed: bool,
+    // Frame rate to hold by lowering the resolution, from `--target-fps`
+    target_fps: Option<f32>,
+    // Resolution divisor picked for the target frame rate, and when it was
+    resolution_divisor: usize,
+    resolution_changed: Instant,
+    // Average frame cost before the last step down in resolution, to check
+    // that it paid off
+    cost_before_coarsening: Option<Duration>,
+    // Coarsest resolution worth going down to. Lowered when a step down
+    // doesn't make frames any cheaper, e.g. when drawing dominates.
+    coarsest_divisor: usize,
+    // Saves the first app's frames for `--timelapse`
+    timelapse: Option<Timelapse>,
+    // Terminals from `--output` showing the same frames, and the size of the
+    // frame they and the main terminal all fit
+    mirrors: Mirrors,
+    frame_size: Option<(u16, u16)>,
+    // Where `--events-json` writes each change
+    events: Option<EventLog>,
+    // Where `--ambient-out` writes the flame's color every frame
+    ambient: Option<AmbientOut>,
+    // Changes from `--script` still to come
+    script: Option<Script>,
+    // Puts the fire out and lights it again for `--cycle`
+    cycle: Option<Cycle>,
+    // Stokes the fire with the machine's load for `--react-load`
+    load: Option<LoadMeter>,
+    // Holds each frame to `--max-bytes-per-frame`
+    frame_budget: Option<FrameBudget>,
+    // When `--auto-palette-time` last matched the palette to the clock. Off
+    // once the palette is changed by hand.
+    auto_palette: Option<Instant>,
+    // Where each app's fire was last drawn, for finding the cell under the
+    // mouse
+    pane_areas: Vec<Rect>,
+    // Radius of the patch a click lights with `--mouse`, in columns
+    brush_radius: usize,
+}
+
+impl Session {
+    fn new(debug: bool, pause_unfocused: bool, max_cpu: Option<u8>) -> Session {
+        Session {
+            debug,
+            borders: Borders::ALL,
+            saved_config: None,
+            persist_path: None,
+            pause_unfocused,
+            unfocused: false,
+            paused_by_user: false,
+            frozen: false,
+            hard_frozen: false,
+            redraw_frozen: false,
+            blown_out: false,
+            quit: false,
+            lock_until: None,
+            last_quit_press: None,
+            terminated: Arc::new(AtomicBool::new(false)),
+            bindings: KeyBindings::default(),
+            tick_rate: TICK_RATE,
+            tick_jitter: Duration::ZERO,
+            cpu_budget: max_cpu.map(|percent| percent as f32 / 100.0),
+            average_frame_cost: Duration::ZERO,
+            average_frame_interval: TICK_RATE,
+            pause_changed: false,
+            target_fps: None,
+            resolution_divisor: 1,
+            resolution_changed: Instant::now(),
+            cost_before_coarsening: None,
+            coarsest_divisor: MAX_RESOLUTION_DIVISOR,
+            timelapse: None,
+            mirrors: Mirrors::default(),
+            frame_size: None,
+            events: None,
+            ambient: None,
+            script: None,
+            cycle: None,
+            load: None,
+            frame_budget: None,
+            auto_palette: None,
+            pane_areas: Vec::new(),
+            brush_radius: BRUSH_RADIUS,
         }
+    }
+
+    /// Animates at a steady `fps` frames per second.
+    fn set_fps(&mut self, fps: u16) {
+        self.tick_rate = Duration::from_secs(1).div_f32(fps as f32);
+        self.average_frame_interval = self.tick_rate;
+    }
+
+    /// Animates at `fps` frames per second, adapting the resolution to keep up.
+    fn set_target_fps(&mut self, fps: u16) {
+        self.target_fps = Some(fps as f32);
+        self.set_fps(fps);
+    }
+
+    /// Frames per second shown lately.
+    fn measured_fps(&self) -> f32 {
+        1.0 / self.average_frame_interval.as_secs_f32()
+    }
+
+    /// Whether the loop should stop, from a key press or a signal.
+    fn should_quit(&self) -> bool {
+        self.quit || self.terminated.load(Ordering::Relaxed)
+    }
+
+    /// Picks how long to wait for the next frame.
+    fn next_interval(&self) -> Duration {
+        if self.tick_jitter.is_zero() {
+            return self.tick_rate;
+        }
+        let jitter = self.tick_jitter.as_micros() as i64;
+        let interval = self.tick_rate.as_micros() as i64 + random_range(-jitter..=jitter);
+        Duration::from_micros(interval.max(0) as u64)
+    }
+
+    /// Books a frame shown `interval` after the last one that took `cost` to
+    /// simulate and draw.
+    fn record_frame(&mut self, interval: Duration, cost: Duration) {
+        // Exponential moving averages, so single slow frames don't cause jumps
+        if !std::mem::take(&mut self.pause_changed) {
+            self.average_frame_interval =
+                self.average_frame_interval.mul_f32(0.9) + interval.mul_f32(0.1);
+        }
+        self.average_frame_cost = self.average_frame_cost.mul_f32(0.9) + cost.mul_f32(0.1);
+        self.throttle();
+    }
+
+    /// Applies the CPU budget. When frames get too expensive for it, the tick
+    /// rate is stretched so the work is spread over a longer interval; it
+    /// recovers as frames get cheaper.
+    fn throttle(&mut self) {
+        if let Some(budget) = self.cpu_budget {
+            self.tick_rate = self.average_frame_cost.div_f32(budget).max(TICK_RATE);
+        }
+    }
+
+    /// Picks a new resolution divisor when the frame rate has fallen behind
+    /// the target, or the frames have become cheap enough to afford more
+    /// detail. Returns `None` when the resolution should stay.
+    fn adapt_resolution(&mut self) -> Option<usize> {
+        let target = self.target_fps?;
+        // Give the averages time to settle after each change
+        if self.resolution_changed.elapsed() < RESOLUTION_SETTLE_TIME {
+            return None;
+        }
+        let divisor = self.resolution_divisor;
+        if let Some(previous_cost) = self.cost_before_coarsening.take()
+            && self.average_frame_cost >= previous_cost.mul_f32(0.95)
+        {
+            // The detail was lost for nothing, so take it back
+            self.coarsest_divisor = divisor - 1;
+            return Some(self.change_resolution(divisor - 1));
+        }
+
+        let budget = 1.0 / target;
+        // Simulating costs about the square of the resolution, so this
+        // overestimates the cost of the next finer step as drawing doesn't
+        // scale with it
+        let finer = (divisor > 1).then(|| {
+            let scale = divisor as f32 / (divisor - 1) as f32;
+            self.average_frame_cost.as_secs_f32() * scale * scale
+        });
+        if self.measured_fps() < target * 0.9 && divisor < self.coarsest_divisor {
+            self.cost_before_coarsening = Some(self.average_frame_cost);
+            Some(self.change_resolution(divisor + 1))
+        } else if finer.is_some_and(|cost| cost < budget * 0.7) {
+            Some(self.change_resolution(divisor - 1))
+        } else {
+            None
+        }
+    }
 
-        self.fire_grid = next_grid;
+    fn change_resolution(&mut self, divisor: usize) -> usize {
+        self.resolution_divisor = divisor;
+        self.resolution_changed = Instant::now();
+        divisor
     }
 
-    fn render_fire(&self) -> Text<'_> {
-        let mut lines = Vec::with_capacity(self.height);
+    /// Whether the animation is currently stopped.
+    fn paused(&self) -> bool {
+        self.paused_by_user || self.hard_frozen || (self.pause_unfocused && self.unfocused)
+    }
+
+    /// Whether the next frame should be drawn. While hard frozen only one
+    /// frame is, until a key press or resize calls for another.
+    fn should_draw(&mut self) -> bool {
+        !self.hard_frozen || mem::take(&mut self.redraw_frozen)
+    }
+
+    /// Books a press of the quit key and says whether it should quit. While
+    /// `--lock` holds, that takes a second press shortly after the first.
+    fn confirm_quit(&mut self) -> bool {
+        let now = Instant::now();
+        if self.lock_until.is_none_or(|until| now >= until) {
+            return true;
+        }
+        let confirmed = self
+            .last_quit_press
+            .is_some_and(|last| now - last <= QUIT_CONFIRM_WINDOW);
+        self.last_quit_press = Some(now);
+        confirmed
+    }
+}
+
+/// Draws the apps to the terminal and to any `--output` mirrors, all at the
+/// size they share.
+fn draw_frame<B: Backend>(
+    terminal: &mut Terminal<B>,
+    apps: &mut [App],
+    session: &mut Session,
+) -> io::Result<()> {
+    let mut frame_size = None;
+    if !session.mirrors.is_empty() {
+        let size = terminal.size()?;
+        frame_size = Some(match session.mirrors.size()? {
+            Some((columns, rows)) => (size.width.min(columns), size.height.min(rows)),
+            None => (size.width, size.height),
+        });
+        session.frame_size = frame_size;
+    }
+    // Taken out of the session for a moment, since drawing reads the session
+    let mut frame_budget = session.frame_budget.take();
+    let mut pane_areas = Vec::new();
+    let drawn = terminal.draw(|f| {
+        pane_areas = draw(f, apps, session);
+        if let Some(frame_budget) = &mut frame_budget {
+            frame_budget.fit(f.buffer_mut());
+        }
+    });
+    session.frame_budget = frame_budget;
+    session.pane_areas = pane_areas;
+    drawn.map_err(hung_up)?;
+    let Some(frame_size) = frame_size else {
+        return Ok(());
+    };
+    let mut mirrors = mem::take(&mut session.mirrors);
+    let drawn = mirrors.draw(frame_size, |f| {
+        draw(f, apps, session);
+    });
+    session.mirrors = mirrors;
+    drawn
+}
 
-        for y in 0..self.height {
-            let mut spans = Vec::with_capacity(self.width);
-            for x in 0..self.width {
-                let heat = self.fire_grid[y][x];
+/// Draws every app side by side, each in its own pane with the chosen
+/// borders, and returns the area each fire was drawn in.
+fn draw(f: &mut Frame, apps: &mut [App], session: &Session) -> Vec<Rect> {
+    let mut area = f.area();
+    if let Some((columns, rows)) = session.frame_size {
+        area.width = area.width.min(columns);
+        area.height = area.height.min(rows);
+    }
+    let panes = Layout::horizontal(vec![Constraint::Fill(1); apps.len()]).split(area);
+    (apps.iter_mut().zip(panes.iter()))
+        .map(|(app, &area)| draw_pane(f, app, area, session))
+        .collect()
+}
 
-                let char_index = (heat as f32 / 255.0 * (self.char_map.len() - 1) as f32) as usize;
-                let char_random_index = random_range(0..self.char_map[char_index].len());
-                let character = self.char_map[char_index][char_random_index];
+/// Draws one app in `area`, returning the area inside the borders that the
+/// fire took.
+fn draw_pane(f: &mut Frame, app: &mut App, area: Rect, session: &Session) -> Rect {
+    let mut block = Block::default().borders(session.borders);
+    if session.debug {
+        // The debug overlay sits in the top border, out of the flame's way
+        let (width, height) = app.grid_size();
+        let fps = match session.target_fps {
+            Some(target) => format!(
+                "{:.1}/{target} fps | res 1/{}",
+                session.measured_fps(),
+                app.resolution_divisor()
+            ),
+            None => format!("{:.1} fps", session.measured_fps()),
+        };
+        let fps = if session.hard_frozen {
+            format!("{fps} | frozen")
+        } else {
+            fps
+        };
+        block = block.title(format!(
+            " {width}x{height} | ramp: {} | {fps} ",
+            app.config.char_ramp.name()
+        ));
+    }
+    if app.config.transparent {
+        block = block.style(Style::default().fg(Color::Reset).bg(Color::Reset));
+    } else if let Some(background) = app.config.background() {
+        block = block.style(Style::default().bg(background));
+    }
+    f.render_widget(&block, area);
+    let inner = block.inner(area);
+    f.render_stateful_widget(FireWidget, inner, app);
+    if session.debug && app.wind_view() {
+        draw_wind_view(f.buffer_mut(), app, inner);
+    }
 
-                let color_index =
-                    (heat as f32 / 255.0 * (self.color_map.len() - 1) as f32) as usize;
-                let color = self.color_map[color_index];
+    if let Some(message) = app.toast() {
+        // Tucked into the top right corner, where the flame rarely reaches
+        let label = format!(" {message} ");
+        let width = (label.chars().count() as u16).min(inner.width);
+        let corner = Rect::new(inner.right() - width, inner.y, width, inner.height.min(1));
+        f.render_widget(Paragraph::new(label).reversed(), corner);
+    }
+    inner
+}
 
-                spans.push(Span::styled(
-                    character.to_string(),
-                    Style::default().fg(color),
-                ));
+/// Draws an arrow every few cells over the flame in `area`, pointing the way
+/// the wind, gusts and turbulence carry the heat rising through it, so you
+/// can see why the flame leans. Only the arrows are drawn; the flame shows
+/// through around them and keeps its colors beneath. Turned or flipped
+/// frames get no arrows, since the field wouldn't line up with them.
+fn draw_wind_view(buf: &mut Buffer, app: &App, area: Rect) {
+    if app.config.rotate || app.flipped() {
+        return;
+    }
+    let (width, height) = app.grid_size();
+    let rows = area.height.saturating_sub(app.config.reflect_rows as u16);
+    for row in (WIND_VIEW_ROWS / 2..rows).step_by(WIND_VIEW_ROWS as usize) {
+        for column in (WIND_VIEW_COLUMNS / 2..area.width).step_by(WIND_VIEW_COLUMNS as usize) {
+            let x = column as usize * width / area.width as usize;
+            let y = (row as usize * height / rows as usize).min(height.saturating_sub(2));
+            let drift = app.drift_at(x, y);
+            let arrow = match drift {
+                ..-1.0 => '←',
+                ..-0.15 => '↖',
+                ..=0.15 => '↑',
+                ..=1.0 => '↗',
+                _ => '→',
+            };
+            // Stronger drifts stand out brighter
+            let shade = (120.0 + drift.abs().min(1.5) * 90.0) as u8;
+            if let Some(cell) = buf.cell_mut((area.x + column, area.y + row)) {
+                cell.set_char(arrow).set_fg(Color::Rgb(shade, shade, 255));
             }
-            lines.push(Line::from(spans));
         }
-        Text::from(lines)
     }
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
-    let tick_rate = Duration::from_millis(60); // ~16.6 FPS
-    let mut last_tick = Instant::now();
+/// Books a finished frame, shown `interval` after the last one, moves any
+/// `--cycle` on, follows the load for `--react-load`, points out a blown out
+/// fire, adapts the apps' resolution for `--target-fps`, takes any
+/// `--timelapse` shot due and writes the `--ambient-out` color.
+fn end_frame(apps: &mut [App], session: &mut Session, interval: Duration, cost: Duration) {
+    session.record_frame(interval, cost);
+    run_script(apps, session);
+    follow_clock(apps, session);
+    if let Some(cycle) = &mut session.cycle {
+        cycle.advance(apps, interval);
+    }
+    if let Some(load) = &session.load {
+        for app in apps.iter_mut() {
+            app.set_load(load.level());
+        }
+    }
+    note_blown_out(apps, session);
+    if let (Some(timelapse), Some(app)) = (&mut session.timelapse, apps.first()) {
+        timelapse.capture(app);
+    }
+    if let (Some(ambient), Some(app)) = (&mut session.ambient, apps.first()) {
+        ambient.write(app.dominant_color());
+    }
+    if let Some(divisor) = session.adapt_resolution() {
+        for app in apps {
+            app.set_resolution_divisor(divisor);
+        }
+    }
+}
 
-    loop {
-        terminal.draw(|f| {
-            let area = f.area();
-            let block = Block::default().borders(Borders::ALL);
-            f.render_widget(&block, area);
-            let inner_area = block.inner(area);
-            app.resize(inner_area.width as usize, inner_area.height as usize);
-
-            let fire_text = app.render_fire();
-            let paragraph = Paragraph::new(fire_text).alignment(Alignment::Center);
-            f.render_widget(paragraph, inner_area);
-        })?;
+/// Points out a fire that has blown out, burning near white all over, each
+/// time it happens, on the fire and in any `--events-json` log.
+fn note_blown_out(apps: &mut [App], session: &mut Session) {
+    let blown_out = apps.iter().any(App::blown_out);
+    if blown_out && !session.blown_out {
+        for app in apps.iter_mut().filter(|app| app.blown_out()) {
+            app.show_toast(if app.config.auto_dampen {
+                "blown out: cooling it down"
+            } else {
+                "blown out: lower the rise or self weight, or pass --auto-dampen"
+            });
+        }
+        if let Some(events) = &mut session.events {
+            events.log("blown_out", "mean heat near white");
+        }
+    }
+    session.blown_out = blown_out;
+}
 
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+/// Moves every app on by `dt`, or only re-picks their glyphs while frozen.
+fn advance_apps(apps: &mut [App], session: &Session, dt: Duration) {
+    for app in apps {
+        if session.frozen {
+            app.shimmer();
+        } else {
+            app.advance(dt);
+        }
+    }
+}
 
-        if event::poll(timeout)? {
-            match event::read()? {
-                CrosstermEvent::Key(key) => {
-                    if key.kind == KeyEventKind::Press {
-                        match key.code {
-                            KeyCode::Char('q') => return Ok(()),
-                            KeyCode::Char('c') if key.modifiers == event::KeyModifiers::CONTROL => {
-                                return Ok(());
-                            }
-                            _ => {}
-                        }
+/// Applies a terminal event. Keys that change the fire apply to every app.
+fn handle_event(event: CrosstermEvent, apps: &mut [App], session: &mut Session) {
+    match event {
+        CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => {
+            session.redraw_frozen = true;
+            // Ctrl-C always quits, whatever `c` is bound to
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers == event::KeyModifiers::CONTROL;
+            // Most terminals send Shift+Space as a plain space, but where
+            // the shift comes through it hard freezes
+            let shift_space =
+                key.code == KeyCode::Char(' ') && key.modifiers == event::KeyModifiers::SHIFT;
+            let action = if ctrl_c {
+                Some(Action::Quit)
+            } else if shift_space {
+                Some(Action::HardFreeze)
+            } else {
+                session.bindings.action(key.code)
+            };
+            match action {
+                Some(Action::Quit) if !ctrl_c && !session.confirm_quit() => {
+                    for app in apps.iter_mut() {
+                        app.show_toast("press again to quit");
+                    }
+                    log_action(Action::Quit, apps, session);
+                }
+                Some(action) => apply_action(action, apps, session),
+                None => {}
+            }
+        }
+        CrosstermEvent::Mouse(mouse) => {
+            session.redraw_frozen = true;
+            let resize_brush = mouse.modifiers.contains(event::KeyModifiers::CONTROL);
+            match mouse.kind {
+                MouseEventKind::ScrollUp | MouseEventKind::ScrollDown if resize_brush => {
+                    session.brush_radius = if mouse.kind == MouseEventKind::ScrollUp {
+                        (session.brush_radius + 1).min(MAX_BRUSH_RADIUS)
+                    } else {
+                        session.brush_radius.saturating_sub(1)
+                    };
+                    for app in apps.iter_mut() {
+                        app.show_toast(format!("brush: {}", session.brush_radius));
                     }
                 }
-                CrosstermEvent::Resize(width, height) => {
-                    eprintln!("Resizing to {}x{}", width, height);
-                    terminal.autoresize()?;
+                MouseEventKind::ScrollUp => apply_action(Action::IntensityUp, apps, session),
+                MouseEventKind::ScrollDown => apply_action(Action::IntensityDown, apps, session),
+                MouseEventKind::Down(MouseButton::Left)
+                | MouseEventKind::Drag(MouseButton::Left) => {
+                    let position = Position::new(mouse.column, mouse.row);
+                    for (app, area) in apps.iter_mut().zip(&session.pane_areas) {
+                        if area.contains(position) {
+                            let (column, row) = (position.x - area.x, position.y - area.y);
+                            app.paint(column as usize, row as usize, session.brush_radius);
+                        }
+                    }
                 }
                 _ => {}
             }
         }
-
-        if last_tick.elapsed() >= tick_rate {
-            app.update_fire();
-            last_tick = Instant::now();
+        CrosstermEvent::FocusLost | CrosstermEvent::FocusGained => {
+            session.unfocused = matches!(event, CrosstermEvent::FocusLost);
+            session.pause_changed = true;
+        }
+        CrosstermEvent::Resize(width, height) => {
+            session.redraw_frozen = true;
+            // The terminal picks up the new size on the next draw
+            eprintln!("Resizing to {}x{}", width, height);
+            if let Some(events) = &mut session.events {
+                events.log("resize", &format!("{width}x{height}"));
+            }
         }
+        _ => {}
     }
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
-
-    let (initial_width, initial_height) = terminal_size()?;
-    let app = App::new(initial_width as usize, initial_height as usize);
-    let res = run_app(&mut terminal, app);
-
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+/// Does what an action's key does, for a key press or a `--script` cue.
+fn apply_action(action: Action, apps: &mut [App], session: &mut Session) {
+    match action {
+        Action::Quit => session.quit = true,
+        Action::Pause => {
+            session.paused_by_user = !session.paused_by_user;
+            session.pause_changed = true;
+        }
+        Action::Freeze => session.frozen = !session.frozen,
+        Action::HardFreeze => {
+            session.hard_frozen = !session.hard_frozen;
+            session.pause_changed = true;
+        }
+        Action::SaveConfig => {
+            session.saved_config = apps.first().map(|app| app.config.clone());
+        }
+        Action::PersistConfig => {
+            let message = match persist_config(apps, session) {
+                Ok(path) => format!("saved to {}", path.display()),
+                Err(err) => format!("can't save: {err}"),
+            };
+            for app in apps.iter_mut() {
+                app.show_toast(message.clone());
+            }
+        }
+        action => {
+            if matches!(action, Action::PaletteNext | Action::Randomize) {
+                session.auto_palette = None;
+            }
+            for app in apps.iter_mut() {
+                handle_app_action(action, app, session);
+            }
+        }
+    }
+    log_action(action, apps, session);
+}
 
-    if let Err(err) = res {
-        eprintln!("{err:?}");
+/// Moves the palette on with the time of day for `--auto-palette-time`, rotating
+/// its hue for the other `--split` panes like [`build_panes`].
+fn follow_clock(apps: &mut [App], session: &mut Session) {
+    if (session.auto_palette).is_none_or(|checked| checked.elapsed() < AUTO_PALETTE_INTERVAL) {
+        return;
+    }
+    session.auto_palette = Some(Instant::now());
+    let palette = palette::time_of_day(local_hour());
+    let panes = apps.len();
+    for (pane, app) in apps.iter_mut().enumerate() {
+        let mut pane_palette = palette.clone();
+        if pane > 0 {
+            let degrees = 360.0 * pane as f32 / panes as f32;
+            pane_palette = (palette.iter())
+                .map(|&color| palette::rotate_hue(color, degrees))
+                .collect();
+        }
+        if pane_palette != app.config.palette {
+            app.set_palette(pane_palette);
+        }
+    }
+}
+
+/// Hours since local midnight, e.g. 13.5 at half past one.
+#[cfg(unix)]
+fn local_hour() -> f32 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs()) as libc::time_t;
+    // SAFETY: all zeroes is a valid `tm`, with a null zone name
+    let mut local: libc::tm = unsafe { mem::zeroed() };
+    // SAFETY: both pointers are to live locals, and `localtime_r` only writes
+    // to the `tm` it is given
+    if unsafe { libc::localtime_r(&now, &mut local) }.is_null() {
+        return (now % 86_400) as f32 / 3600.0;
+    }
+    local.tm_hour as f32 + local.tm_min as f32 / 60.0
+}
+
+/// Hours since midnight UTC, without a portable way to find the time zone.
+#[cfg(not(unix))]
+fn local_hour() -> f32 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs());
+    (now % 86_400) as f32 / 3600.0
+}
+
+/// Applies the `--script` cues that have come due.
+fn run_script(apps: &mut [App], session: &mut Session) {
+    let cues = match &mut session.script {
+        Some(script) => script.due(),
+        None => return,
+    };
+    for cue in cues {
+        match cue {
+            Cue::Action(action) => apply_action(action, apps, session),
+            Cue::Set(key, value) => {
+                if key == "palette" {
+                    session.auto_palette = None;
+                }
+                let shown = value
+                    .as_str()
+                    .map_or_else(|| value.to_string(), str::to_string);
+                for app in apps.iter_mut() {
+                    let mut config = app.config.clone();
+                    match config.set(&key, value.clone()) {
+                        Ok(()) => {
+                            app.set_config(config);
+                            app.show_toast(format!("{key}: {shown}"));
+                        }
+                        // It suited the defaults but not this config
+                        Err(err) => app.show_toast(format!("can't set {key}: {err}")),
+                    }
+                }
+                if let Some(events) = &mut session.events {
+                    events.log(&key, &shown);
+                }
+            }
+        }
+    }
+}
+
+/// Writes the first app's settings to the config file for `W`, creating it
+/// and its directory if needed, and returns where they went.
+fn persist_config<'a>(apps: &[App], session: &'a Session) -> io::Result<&'a Path> {
+    let (Some(path), Some(app)) = (&session.persist_path, apps.first()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no home directory for the config file",
+        ));
+    };
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, app.config.to_toml())?;
+    Ok(path)
+}
+
+/// Where settings are loaded from without `--config` and saved to by `W`:
+/// `fitt/config.toml` in `$XDG_CONFIG_HOME`, or in `~/.config` without it.
+fn default_config_path() -> Option<PathBuf> {
+    let dir = (env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(dir.join("fitt").join("config.toml"))
+}
+
+/// Writes a handled action to the `--events-json` log, along with where it
+/// left things.
+fn log_action(action: Action, apps: &[App], session: &mut Session) {
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    let value = match action {
+        Action::Pause => on_off(session.paused_by_user),
+        Action::Freeze => on_off(session.frozen),
+        Action::HardFreeze => on_off(session.hard_frozen),
+        Action::Quit if session.quit => "quit",
+        Action::SaveConfig => "saved",
+        // Everything else reports its new value in a toast
+        _ => apps.first().and_then(App::toast).unwrap_or_default(),
+    }
+    .to_string();
+    if let Some(events) = &mut session.events {
+        let name = toml::Value::try_from(action).map_or_else(
+            |_| format!("{action:?}"),
+            |name| name.as_str().unwrap_or_default().to_string(),
+        );
+        events.log(&name, &value);
+    }
+}
+
+/// Applies an action that acts on each pane separately, and shows what
+/// changed in a toast.
+fn handle_app_action(action: Action, app: &mut App, session: &Session) {
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+    match action {
+        Action::AgeTint => {
+            app.config.age_tint = !app.config.age_tint;
+            app.show_toast(format!("age tint: {}", on_off(app.config.age_tint)));
+        }
+        Action::GlyphStability => {
+            app.config.glyph_stability = !app.config.glyph_stability;
+            let enabled = on_off(app.config.glyph_stability);
+            app.show_toast(format!("glyph stability: {enabled}"));
+        }
+        Action::Replay => app.toggle_replay(),
+        Action::RampNext => {
+            app.set_char_ramp(app.config.char_ramp.next());
+            app.show_toast(format!("ramp: {}", app.config.char_ramp.name()));
+        }
+        Action::BlocksToggle => {
+            app.toggle_blocks();
+            app.show_toast(format!("ramp: {}", app.config.char_ramp.name()));
+        }
+        Action::SafeGlyphs => {
+            app.toggle_safe_glyphs();
+            app.show_toast(format!("ramp: {}", app.config.char_ramp.name()));
+        }
+        Action::AlignNext => {
+            app.cycle_alignment();
+            let side = match app.alignment() {
+                Alignment::Left => "left",
+                Alignment::Center => "center",
+                Alignment::Right => "right",
+            };
+            app.show_toast(format!("align: {side}"));
+        }
+        Action::PaletteNext => {
+            app.cycle_palette();
+            let name = Preset::find(&app.config.palette).map_or("custom", Preset::name);
+            app.show_toast(format!("palette: {name}"));
+        }
+        Action::GrayView => {
+            app.toggle_gray_view();
+            app.show_toast(if app.gray_view() {
+                "grayscale heat"
+            } else {
+                "colors restored"
+            });
+        }
+        Action::FallingNext => {
+            app.config.falling = Falling::next(app.config.falling);
+            let name = app.config.falling.map_or("off", Falling::name);
+            app.show_toast(format!("falling: {name}"));
+        }
+        Action::EffectNext => {
+            app.cycle_effect();
+            let name = app.config.effect.map_or("fire", Effect::name);
+            app.show_toast(format!("effect: {name}"));
+        }
+        Action::Flip => {
+            app.toggle_flip();
+            app.show_toast(if app.flipped() {
+                "upside down"
+            } else {
+                "upright"
+            });
+        }
+        Action::Flare => app.flare_up(),
+        Action::Relight => {
+            app.relight();
+            app.show_toast("relighting");
+        }
+        // Zoomed in, the arrow keys pan the view instead of blowing the flame
+        Action::WindLeft if app.zoom() > 1 => app.pan(-1, 0),
+        Action::WindRight if app.zoom() > 1 => app.pan(1, 0),
+        Action::PanUp => app.pan(0, -1),
+        Action::PanDown => app.pan(0, 1),
+        Action::ZoomIn | Action::ZoomOut if session.paused() || app.zoom() > 1 => {
+            if action == Action::ZoomIn {
+                app.zoom_in();
+            } else {
+                app.zoom_out();
+            }
+            app.show_toast(format!("zoom: {}x", app.zoom()));
+        }
+        Action::WindLeft => {
+            app.gust(-GUST_IMPULSE);
+            app.show_toast("gust: left");
+        }
+        Action::WindRight => {
+            app.gust(GUST_IMPULSE);
+            app.show_toast("gust: right");
+        }
+        Action::SelfWeightDown | Action::SelfWeightUp => {
+            let step = if action == Action::SelfWeightDown {
+                -SELF_WEIGHT_STEP
+            } else {
+                SELF_WEIGHT_STEP
+            };
+            app.nudge_self_weight(step);
+            app.show_toast(format!("self weight: {:.2}", app.config.self_weight));
+        }
+        Action::SideDiffusionDown | Action::SideDiffusionUp => {
+            let step = if action == Action::SideDiffusionDown {
+                -SIDE_DIFFUSION_STEP
+            } else {
+                SIDE_DIFFUSION_STEP
+            };
+            app.nudge_side_diffusion(step);
+            app.show_toast(format!("side diffusion: {:.3}", app.config.side_diffusion));
+        }
+        Action::IntensityDown | Action::IntensityUp => {
+            let step = if action == Action::IntensityDown {
+                -INTENSITY_STEP
+            } else {
+                INTENSITY_STEP
+            };
+            app.nudge_intensity(step);
+            app.show_toast(format!("intensity: {:.2}", app.config.intensity));
+        }
+        Action::Randomize => {
+            app.randomize();
+            app.show_toast("randomized");
+        }
+        Action::HeatView if session.debug => app.toggle_heat_view(),
+        Action::WindView if session.debug => app.toggle_wind_view(),
+        _ => {}
+    }
+}
+
+/// Runs the animation until the user quits.
+#[cfg(not(feature = "async"))]
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    apps: &mut [App],
+    session: &mut Session,
+    events: &mut impl EventSource,
+) -> io::Result<()> {
+    let mut last_tick = Instant::now();
+    let mut interval = session.next_interval();
+
+    loop {
+        let frame_start = Instant::now();
+        if session.should_draw() {
+            draw_frame(terminal, apps, session)?;
+        }
+        let mut frame_cost = frame_start.elapsed();
+
+        let timeout = interval
+            .checked_sub(last_tick.elapsed())
+            .unwrap_or_else(|| Duration::from_secs(0));
+
+        if session.paused() {
+            // Nothing changes while paused, so sleep until the next event,
+            // only waking up to check for signals or to take down a toast
+            let toasting = apps.iter().any(|app| app.toast().is_some());
+            loop {
+                if events.poll(SIGNAL_CHECK_INTERVAL)? {
+                    handle_event(events.read()?, apps, session);
+                    break;
+                }
+                if session.should_quit() {
+                    return Ok(());
+                }
+                if toasting {
+                    break;
+                }
+            }
+        } else if events.poll(timeout)? {
+            handle_event(events.read()?, apps, session);
+        }
+        if session.should_quit() {
+            return Ok(());
+        }
+
+        if !session.paused() && last_tick.elapsed() >= interval {
+            let update_start = Instant::now();
+            let dt = last_tick.elapsed();
+            advance_apps(apps, session, dt);
+            frame_cost += update_start.elapsed();
+            end_frame(apps, session, dt, frame_cost);
+            last_tick = Instant::now();
+            interval = session.next_interval();
+        }
+    }
+}
+
+/// Lets the fire die down to a bed of embers that fades out over
+/// `EMBER_OUTRO`, for `--ember-outro`. Any key skips to the end.
+fn run_outro<B: Backend>(
+    terminal: &mut Terminal<B>,
+    apps: &mut [App],
+    session: &mut Session,
+    events: &mut impl EventSource,
+) -> io::Result<()> {
+    let start = Instant::now();
+    let mut last_tick = start;
+    while start.elapsed() < EMBER_OUTRO {
+        let faded = start.elapsed().as_secs_f32() / EMBER_OUTRO.as_secs_f32();
+        for app in apps.iter_mut() {
+            app.smolder((1.0 - faded).powi(2));
+        }
+        draw_frame(terminal, apps, session)?;
+        if events.poll(TICK_RATE)? && matches!(events.read()?, CrosstermEvent::Key(_)) {
+            break;
+        }
+        for app in apps.iter_mut() {
+            app.advance(last_tick.elapsed());
+        }
+        last_tick = Instant::now();
     }
     Ok(())
 }
+
+/// Returns the cells the sixel image covers, as `(columns, rows)`, and the
+/// pixel size of each cell.
+fn sixel_layout() -> io::Result<((u16, u16), (usize, usize))> {
+    let size = window_size()?;
+    // Leave the last row empty so the image never scrolls the screen
+    let area = (size.columns, size.rows.saturating_sub(1));
+    let cell = match (size.width, size.height) {
+        (0, _) | (_, 0) => SIXEL_FALLBACK_CELL,
+        (width, height) => (
+            (width / size.columns) as usize,
+            (height / size.rows) as usize,
+        ),
+    };
+    Ok((area, cell))
+}
+
+/// Runs the animation as sixel graphics, written straight to the terminal
+/// rather than drawn through ratatui. Only the first app is shown.
+fn run_sixel(
+    out: &mut impl Write,
+    apps: &mut [App],
+    session: &mut Session,
+    events: &mut impl EventSource,
+) -> io::Result<()> {
+    let mut last_tick = Instant::now();
+    let mut interval = session.next_interval();
+    let mut last_size = None;
+
+    loop {
+        let frame_start = Instant::now();
+        let ((columns, rows), (cell_width, cell_height)) = sixel_layout()?;
+        if last_size != Some((columns, rows)) {
+            queue!(out, Clear(ClearType::All))?;
+            apps[0].resize(columns as usize, rows as usize);
+            last_size = Some((columns, rows));
+        }
+        if session.should_draw() {
+            queue!(out, MoveTo(0, 0))?;
+            write!(out, "{}", apps[0].to_sixel(cell_width, cell_height))?;
+            out.flush()?;
+        }
+        let mut frame_cost = frame_start.elapsed();
+
+        // Wait for the next frame, or just for events and signals while paused
+        loop {
+            let timeout = if session.paused() {
+                SIGNAL_CHECK_INTERVAL
+            } else {
+                interval.saturating_sub(last_tick.elapsed())
+            };
+            if events.poll(timeout)? {
+                handle_event(events.read()?, apps, session);
+            }
+            if session.should_quit() {
+                return Ok(());
+            }
+            if !session.paused() && last_tick.elapsed() >= interval {
+                break;
+            }
+        }
+
+        let update_start = Instant::now();
+        let dt = last_tick.elapsed();
+        advance_apps(apps, session, dt);
+        frame_cost += update_start.elapsed();
+        end_frame(apps, session, dt, frame_cost);
+        last_tick = Instant::now();
+        interval = session.next_interval();
+    }
+}
+
+/// Runs the animation in the bottom `rows` rows of the normal screen. The
+/// rows above are made the scrolling region so the shell's output never
+/// runs into the fire, and the cursor is put back after every frame.
+fn run_strip(
+    out: &mut impl Write,
+    app: &mut App,
+    session: &mut Session,
+    rows: u16,
+) -> io::Result<()> {
+    let mut last_size = None;
+    let mut last_tick = Instant::now();
+    loop {
+        let frame_start = Instant::now();
+        let (width, height) = terminal_size()?;
+        let rows = rows.min(height.saturating_sub(1)).max(1);
+        let top = height - rows;
+        if last_size != Some((width, height)) {
+            // Setting the scrolling region homes the cursor, so save it first
+            write!(out, "\x1b7\x1b[1;{top}r\x1b8")?;
+            app.resize(width as usize, rows as usize);
+            last_size = Some((width, height));
+        }
+
+        write!(out, "\x1b7")?;
+        for (row, line) in app.to_ansi_string().lines().enumerate() {
+            queue!(out, MoveTo(0, top + row as u16))?;
+            write!(out, "{line}")?;
+        }
+        write!(out, "\x1b8")?;
+        out.flush()?;
+
+        let dt = last_tick.elapsed();
+        app.advance(dt);
+        last_tick = Instant::now();
+        end_frame(
+            std::slice::from_mut(app),
+            session,
+            dt,
+            frame_start.elapsed(),
+        );
+        let deadline = Instant::now() + session.next_interval();
+        while Instant::now() < deadline {
+            if session.should_quit() {
+                // Wipe the strip and give the whole screen back to the shell
+                write!(out, "\x1b7")?;
+                for row in top..height {
+                    queue!(out, MoveTo(0, row), Clear(ClearType::CurrentLine))?;
+                }
+                write!(out, "\x1b[r\x1b8")?;
+                return out.flush();
+            }
+            std::thread::sleep(
+                deadline
+                    .saturating_duration_since(Instant::now())
+                    .min(SIGNAL_CHECK_INTERVAL),
+            );
+        }
+    }
+}
+
+/// Keeps printing the fire bar, one line per frame, until a signal stops it.
+fn run_bar(out: &mut impl Write, app: &mut App, session: &mut Session) -> io::Result<()> {
+    let mut last_tick = Instant::now();
+    loop {
+        writeln!(out, "{}", app.row_to_ansi_string(BAR_ROW))?;
+        out.flush()?;
+
+        let deadline = Instant::now() + session.next_interval();
+        while Instant::now() < deadline {
+            if session.should_quit() {
+                return Ok(());
+            }
+            std::thread::sleep(
+                deadline
+                    .saturating_duration_since(Instant::now())
+                    .min(SIGNAL_CHECK_INTERVAL),
+            );
+        }
+        app.advance(last_tick.elapsed());
+        last_tick = Instant::now();
+    }
+}
+
+/// Where the loops read terminal events from, so a run can be fed events
+/// of its own.
+trait EventSource {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+    fn read(&mut self) -> io::Result<CrosstermEvent>;
+}
+
+/// The events of the terminal the fire is drawn in.
+struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout).map_err(hung_up)
+    }
+
+    fn read(&mut self) -> io::Result<CrosstermEvent> {
+        event::read().map_err(hung_up)
+    }
+}
+
+/// Marks an error from reading or writing the terminal as
+/// `NotConnected` when it is the EIO a hung-up terminal gives on unix. EIO
+/// from anything else, like a failing disk, is still reported.
+fn hung_up(err: io::Error) -> io::Error {
+    const EIO: i32 = 5;
+    if cfg!(unix) && err.raw_os_error() == Some(EIO) {
+        io::Error::new(io::ErrorKind::NotConnected, err)
+    } else {
+        err
+    }
+}
+
+/// Whether an error means the terminal or the pipe drawn into has gone
+/// away, e.g. with a closed SSH session.
+fn is_disconnect(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::NotConnected
+            | io::ErrorKind::ConnectionReset
+    )
+}
+
+/// Treats errors meaning the terminal has gone away as the end of the run
+/// rather than a failure worth reporting.
+fn end_on_disconnect(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(err) if is_disconnect(&err) => Ok(()),
+        result => result,
+    }
+}
+
+/// Simulates `ticks` frames without drawing them.
+fn warm_up(app: &mut App, ticks: usize) {
+    for _ in 0..ticks {
+        app.update_fire(TICK_RATE);
+    }
+}
+
+/// Resolves the effective config: defaults, then the config file, then flags.
+/// Exits with a usage error if a file can't be loaded.
+fn load_config(cli: &Cli) -> FireConfig {
+    let fail = |message: String| -> ! {
+        Cli::command()
+            .error(ErrorKind::InvalidValue, message)
+            .exit()
+    };
+
+    let config_path =
+        (cli.config.clone()).or_else(|| default_config_path().filter(|path| path.is_file()));
+    let mut config = match &config_path {
+        Some(path) => FireConfig::load(path)
+            .unwrap_or_else(|err| fail(format!("invalid config file '{}': {err}", path.display()))),
+        None => FireConfig::default(),
+    };
+    if cli.retro {
+        config.palette = palette::RETRO.to_vec();
+        config.char_ramp = CharRamp::Blocks;
+    }
+    if cli.blackbody {
+        if cli.blackbody_min >= cli.blackbody_max {
+            fail(format!(
+                "--blackbody-min ({}) must be below --blackbody-max ({})",
+                cli.blackbody_min, cli.blackbody_max
+            ));
+        }
+        config.palette = palette::blackbody(cli.blackbody_min, cli.blackbody_max);
+    }
+    if let Some(colors) = &cli.gradient {
+        config.palette = palette::gradient(colors[0], colors[1], cli.gradient_bands as usize);
+    }
+    if let Some(colormap) = cli.colormap {
+        config.palette = colormap.colors(cli.gradient_bands as usize);
+    }
+    if let Some(preset) = cli.palette {
+        config.palette = preset.colors().to_vec();
+    }
+    if cli.auto_palette_time {
+        config.palette = palette::time_of_day(local_hour());
+    }
+    if let Some(path) = &cli.palette_file {
+        config.palette = palette::load_palette_file(path).unwrap_or_else(|err| {
+            fail(format!("invalid palette file '{}': {err}", path.display()))
+        });
+    }
+    for &(index, color) in &cli.bands {
+        let bands = config.palette.len();
+        match config.palette.get_mut(index) {
+            Some(entry) => *entry = color,
+            None => fail(format!(
+                "--band {index} is out of range for a palette of {bands} colors"
+            )),
+        }
+    }
+    if cli.instant_palette {
+        config.palette_fade_frames = 0;
+    }
+    if cli.mono {
+        config.mono = true;
+    }
+    if cli.dither {
+        config.dither = true;
+    }
+    if let Some(brightness) = cli.brightness {
+        config.brightness = brightness;
+    }
+    if let Some(contrast) = cli.contrast {
+        config.contrast = contrast;
+    }
+    if let Some(vignette) = cli.vignette {
+        config.vignette = vignette;
+    }
+    if cli.gas_flame {
+        config.gas_flame = true;
+    }
+    if let Some(gas_transition) = cli.gas_transition {
+        config.gas_transition = gas_transition;
+    }
+    if let Some(char_ramp) = cli.char_ramp {
+        config.char_ramp = char_ramp;
+    }
+    if cli.align_bands {
+        config.align_bands = true;
+    }
+    if config.align_bands && config.palette.len() != config.char_ramp.char_map().len() {
+        eprintln!(
+            "Resampling the {} glyph bands of the {} ramp to the palette's {} colors",
+            config.char_ramp.char_map().len(),
+            config.char_ramp.name(),
+            config.palette.len()
+        );
+    }
+    if cli.glyph_stability {
+        config.glyph_stability = true;
+    }
+    if let Some(variety) = cli.glyph_variety {
+        config.glyph_variety = variety;
+    }
+    if let Some(threshold) = cli.shimmer_threshold {
+        config.shimmer_threshold = threshold;
+    }
+    if cli.diff_render {
+        config.diff_render = true;
+    }
+    if let Some(blur_trail) = cli.blur_trail {
+        config.blur_trail = blur_trail;
+    }
+    if let Some(bloom) = cli.bloom {
+        config.bloom = bloom;
+    }
+    if cli.age_tint {
+        config.age_tint = true;
+    }
+    if cli.invert {
+        config.invert = true;
+    }
+    if let Some(background) = cli.background {
+        config.background = Some(background);
+    }
+    if let Some(ash) = cli.ash {
+        config.ash = Some(ash);
+    }
+    if let Some(falling) = cli.falling {
+        config.falling = Some(falling);
+    }
+    if cli.transparent {
+        config.transparent = true;
+    }
+    if let Some(viscosity) = cli.viscosity {
+        config.viscosity = viscosity;
+    }
+    if let Some(substeps) = cli.substeps {
+        config.substeps = substeps;
+    }
+    if let Some(edge_falloff) = cli.edge_falloff {
+        config.edge_falloff = edge_falloff;
+    }
+    if let Some(max_height) = cli.max_height {
+        config.max_height = max_height;
+    }
+    if let Some(tip_decay) = cli.tip_decay {
+        config.tip_decay = tip_decay;
+    }
+    if let Some(intensity) = cli.intensity {
+        config.intensity = intensity;
+    }
+    if let Some(diffusion_radius) = cli.diffusion_radius {
+        config.diffusion_radius = diffusion_radius;
+    }
+    if let Some(curve) = cli.base_curve {
+        config.base_curve = curve;
+    }
+    if let Some(exponent) = cli.base_exponent {
+        config.base_exponent = exponent;
+    }
+    if let Some(draft) = cli.draft {
+        config.draft = draft;
+    }
+    if let Some(flare_rate) = cli.flare_rate {
+        config.flare_rate = flare_rate;
+    }
+    if let Some(path) = &cli.source_image {
+        config.injection_profile = source_image::column_brightness(path).unwrap_or_else(|err| {
+            fail(format!("invalid source image '{}': {err}", path.display()))
+        });
+    }
+    if let Some(path) = &cli.burn_mask {
+        let mask = fs::read_to_string(path).unwrap_or_else(|err| {
+            fail(format!("can't read burn mask '{}': {err}", path.display()))
+        });
+        config.burn_mask = mask.lines().map(|row| row.trim_end().to_string()).collect();
+    }
+    if let Some(ignition_build) = cli.ignition_build {
+        config.ignition_build_frames = ignition_build;
+    }
+    if let Some(cooling_noise) = cli.cooling_noise {
+        config.cooling_noise_scale = cooling_noise;
+    }
+    if cli.coherent_flicker {
+        config.coherent_flicker = true;
+    }
+    if let Some(scale) = cli.flicker_scale {
+        config.flicker_scale = scale;
+    }
+    if let Some(speed) = cli.flicker_speed {
+        config.flicker_speed = speed;
+    }
+    if let Some(pulse) = cli.pulse {
+        config.pulse_bpm = pulse;
+    }
+    if let Some(wander) = cli.wander {
+        config.wander = wander;
+    }
+    if let Some(floor_heat) = cli.floor_heat {
+        config.floor_heat = floor_heat;
+    }
+    if let Some(ceil_heat) = cli.ceil_heat {
+        config.ceil_heat = ceil_heat;
+    }
+    if cli.auto_dampen {
+        config.auto_dampen = true;
+    }
+    if let Some(wind) = cli.wind {
+        config.wind = wind;
+    }
+    if cli.auto_wind {
+        config.auto_wind = true;
+    }
+    if let Some(wind_amplitude) = cli.wind_amplitude {
+        config.wind_amplitude = wind_amplitude;
+    }
+    if let Some(wind_period) = cli.wind_period {
+        config.wind_period = wind_period;
+    }
+    if let Some(turbulence) = cli.turbulence {
+        config.turbulence = turbulence;
+    }
+    if cli.wide {
+        config.wide = true;
+    }
+    if cli.braille {
+        config.braille = true;
+    }
+    if cli.rotate {
+        config.rotate = true;
+    }
+    if cli.wrap {
+        config.wrap = true;
+    }
+    if let Some(shape) = cli.shape {
+        config.shape = Some(shape);
+    }
+    if let Some(effect) = cli.effect {
+        config.effect = Some(effect);
+    }
+    if let Some(order) = cli.update_order {
+        config.update_order = order;
+    }
+    if cli.cell_seeding {
+        config.cell_seeding = true;
+    }
+    if let Some(reflect_rows) = cli.reflect {
+        config.reflect_rows = reflect_rows;
+    }
+    if let Some(layers) = cli.layers {
+        config.layers = layers;
+    }
+    if let Some(sim_size) = cli.sim_size {
+        config.sim_size = Some(sim_size);
+    }
+    if let Some(columns) = cli.ultrawide_columns {
+        config.ultrawide_columns = columns;
+    }
+    if cli.target_fps.is_some() {
+        // The frame rate target picks the resolution itself
+        config.ultrawide_columns = 0;
+    }
+    if let Err(err) = config.validate() {
+        fail(err.to_string());
+    }
+    config
+}
+
+fn build_app(
+    cli: &Cli,
+    config: FireConfig,
+    grid: Option<&[Vec<Heat>]>,
+    seed: u64,
+    width: u16,
+    height: u16,
+) -> App {
+    let mut builder = App::builder()
+        .size(width as usize, height as usize)
+        .config(config)
+        .seed(seed)
+        .history(cli.history)
+        .color_mode(cli.colors.unwrap_or_else(ColorMode::detect))
+        .alignment(cli.align);
+    if let Some(glyph_seed) = cli.glyph_seed {
+        builder = builder.glyph_seed(glyph_seed);
+    }
+    if let Some(megabytes) = cli.max_memory {
+        // The budget is shared between the `--split` panes
+        let panes = cli.split.unwrap_or(1) as usize;
+        builder = builder.history_memory(megabytes.saturating_mul(1024 * 1024) / panes);
+    }
+    let mut app = builder.build();
+    if let Some(grid) = grid {
+        app.set_grid(grid);
+    }
+    app
+}
+
+/// Builds one app per `--split` pane. The first pane uses the config as is;
+/// the others rotate the palette's hue and use the next glyph ramps so every
+/// pane looks different.
+fn build_panes(
+    cli: &Cli,
+    config: FireConfig,
+    grid: Option<&[Vec<Heat>]>,
+    seed: u64,
+    width: u16,
+    height: u16,
+) -> Vec<App> {
+    let panes = cli.split.unwrap_or(1) as usize;
+    let pane_width = width / panes as u16;
+    (0..panes)
+        .map(|pane| {
+            let mut pane_config = config.clone();
+            if pane > 0 {
+                let degrees = 360.0 * pane as f32 / panes as f32;
+                pane_config.palette = (config.palette.iter())
+                    .map(|&color| palette::rotate_hue(color, degrees))
+                    .collect();
+                pane_config.char_ramp = CharRamp::ALL[pane % CharRamp::ALL.len()];
+            }
+            build_app(
+                cli,
+                pane_config,
+                grid,
+                seed.wrapping_add(pane as u64),
+                pane_width,
+                height,
+            )
+        })
+        .collect()
+}
+
+/// Prints `OK` if a file checked with `--check-config` or `--check-palette`
+/// loaded, or else why it didn't, and exits with a status to match.
+fn report_check(path: &Path, result: Result<(), impl fmt::Display>) -> ! {
+    match result {
+        Ok(()) => {
+            println!("OK");
+            process::exit(0)
+        }
+        Err(err) => {
+            eprintln!("{}: {err}", path.display());
+            process::exit(1)
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+
+    if let Some(path) = &cli.check_config {
+        report_check(path, FireConfig::load(path).map(drop));
+    }
+    if let Some(path) = &cli.check_palette {
+        report_check(path, palette::load_palette_file(path).map(drop));
+    }
+    // Load everything that can fail before touching the terminal so errors
+    // are printed to a normal screen.
+    let config = load_config(&cli);
+    let script = cli.script.as_ref().map(|path| {
+        Script::load(path).unwrap_or_else(|err| {
+            Cli::command()
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!("invalid script '{}': {err}", path.display()),
+                )
+                .exit()
+        })
+    });
+    let grid = cli.load_grid.as_ref().map(|path| {
+        fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| csv_export::read_grid_csv(&contents))
+            .unwrap_or_else(|err| {
+                Cli::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("invalid grid '{}': {err}", path.display()),
+                    )
+                    .exit()
+            })
+    });
+    let grid = grid.as_deref();
+    // A loaded grid is shown as it is rather than burned on from
+    let once_warmup = if grid.is_some() { 0 } else { ONCE_WARMUP_TICKS };
+    if cli.show_config {
+        print!("{}", config.to_toml());
+        return Ok(());
+    }
+    if let Some(path) = &cli.export_palette {
+        fs::write(path, palette::format_palette(&config.palette))?;
+        return Ok(());
+    }
+    if cli.preview_colors {
+        let (width, _) = terminal_size().unwrap_or((80, 24));
+        print!("{}", palette::preview(&config.palette, width as usize));
+        return Ok(());
+    }
+    let seed = cli.seed.unwrap_or_else(rand::random);
+
+    #[cfg(feature = "gif")]
+    if let Some(path) = &cli.gif {
+        let (width, height) = terminal_size().unwrap_or((80, 24));
+        // GIFs hold any color, whatever the terminal can show
+        let mut app = build_app(&cli, config, grid, seed, width, height)
+            .with_color_mode(ColorMode::Truecolor);
+        warm_up(&mut app, cli.warmup.unwrap_or(once_warmup));
+        gif_export::export(&mut app, path, cli.frames)?;
+        return Ok(());
+    }
+
+    if let Some(path) = &cli.dump_csv {
+        let (width, height) = terminal_size().unwrap_or((80, 24));
+        let mut app = build_app(&cli, config, grid, seed, width, height);
+        warm_up(&mut app, cli.warmup.unwrap_or(once_warmup));
+        csv_export::export(&mut app, path, cli.frames)?;
+        return Ok(());
+    }
+
+    if let Some(path) = &cli.asciicast {
+        let (width, height) = terminal_size().unwrap_or((80, 24));
+        let mut app = build_app(&cli, config, grid, seed, width, height);
+        warm_up(&mut app, cli.warmup.unwrap_or(once_warmup));
+        asciicast::export(&mut app, path, cli.frames, width, height)?;
+        return Ok(());
+    }
+
+    if let Some(width) = cli.bar
+        && !cli.bar_follow
+    {
+        let mut app = build_app(&cli, config, grid, seed, width, BAR_HEIGHT);
+        warm_up(&mut app, cli.warmup.unwrap_or(once_warmup));
+        println!("{}", app.row_to_ansi_string(BAR_ROW));
+        return Ok(());
+    }
+
+    // Entering the alternate screen would only write escape codes into a file
+    // or pipe, so fall back to printing a plain frame.
+    let is_terminal = io::stdout().is_terminal();
+    if cli.bar.is_none() && (cli.once || !is_terminal) {
+        let (width, height) = terminal_size().unwrap_or((80, 24));
+        let mut app = build_app(&cli, config, grid, seed, width, height);
+        warm_up(&mut app, cli.warmup.unwrap_or(once_warmup));
+        if is_terminal || cli.ansi {
+            print!("{}", app.to_ansi_string());
+        } else {
+            print!("{}", app.render_plain());
+        }
+        return Ok(());
+    }
+
+    let mut session = Session::new(cli.debug, cli.pause_unfocused, cli.max_cpu);
+    session.tick_jitter = Duration::from_millis(cli.tick_jitter);
+    session.borders = cli.border;
+    if let Some(fps) = cli.fps {
+        session.set_fps(fps);
+    }
+    if let Some(fps) = cli.target_fps {
+        session.set_target_fps(fps);
+    }
+    session.bindings = KeyBindings::new(&config.keys);
+    if let (Some(interval), Some(dir)) = (cli.timelapse, &cli.out) {
+        session.timelapse = Some(Timelapse::new(dir, interval)?);
+    }
+    session.mirrors = Mirrors::open(&cli.output)?;
+    session.script = script;
+    session.persist_path = (cli.persist_config.clone())
+        .or_else(|| cli.config.clone())
+        .or_else(default_config_path);
+    session.cycle = (cli.cycle).map(|(burn, dark)| Cycle::new(burn, dark));
+    #[cfg(unix)]
+    if cli.react_load {
+        session.load = Some(LoadMeter::start(load::LoadAverage::new()));
+    }
+    session.frame_budget = (cli.max_bytes_per_frame).map(|bytes| FrameBudget::new(bytes as usize));
+    if cli.auto_palette_time {
+        session.auto_palette = Some(Instant::now());
+    }
+    if let Some(path) = &cli.events_json {
+        session.events = Some(EventLog::create(path)?);
+    }
+    if let Some(path) = &cli.ambient_out {
+        session.ambient = Some(AmbientOut::create(path)?);
+    }
+    // Quit through the loop on signals so the terminal is restored afterwards
+    #[cfg(all(unix, feature = "unix"))]
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        signal_hook::flag::register(signal, Arc::clone(&session.terminated))?;
+    }
+
+    if let Some(width) = cli.bar {
+        let mut app = build_app(&cli, config, grid, seed, width, BAR_HEIGHT);
+        warm_up(&mut app, cli.warmup.unwrap_or(once_warmup));
+        end_on_disconnect(run_bar(&mut io::stdout(), &mut app, &mut session).map_err(hung_up))?;
+        return Ok(());
+    }
+
+    // The strip shares the screen with the shell, so it stays out of raw mode
+    // and the alternate screen and never reads the keyboard
+    if let Some(rows) = cli.bottom_strip {
+        let (width, height) = terminal_size()?;
+        let mut app = build_app(&cli, config, grid, seed, width, rows.min(height));
+        warm_up(&mut app, cli.warmup.unwrap_or(0));
+        run_strip(&mut io::stdout(), &mut app, &mut session, rows)?;
+        return Ok(());
+    }
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    // Some minimal terminals have no alternate screen, so the fire takes over
+    // the main screen instead and clears it again on exit
+    let alternate_screen = match execute!(stdout, EnterAlternateScreen) {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("No alternate screen ({err}), drawing on the main screen instead");
+            execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+            false
+        }
+    };
+    if cli.pause_unfocused {
+        execute!(stdout, EnableFocusChange)?;
+    }
+    if cli.mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let (initial_width, initial_height) = terminal_size()?;
+    let mut apps = build_panes(&cli, config, grid, seed, initial_width, initial_height);
+    // Warm up once the apps have the size they are drawn at, since resizing
+    // clears the grid
+    if cli.sixel {
+        let ((columns, rows), _) = sixel_layout()?;
+        apps[0].resize(columns as usize, rows as usize);
+    } else {
+        draw_frame(&mut terminal, &mut apps, &mut session)?;
+    }
+    for app in &mut apps {
+        warm_up(app, cli.warmup.unwrap_or(0));
+    }
+    session.paused_by_user = cli.start_paused;
+    session.lock_until = cli.lock.map(|lock| Instant::now() + lock);
+
+    let res = if cli.sixel {
+        execute!(terminal.backend_mut(), Hide)?;
+        run_sixel(
+            terminal.backend_mut(),
+            &mut apps,
+            &mut session,
+            &mut TerminalEvents,
+        )
+        .map_err(hung_up)
+    } else {
+        #[cfg(not(feature = "async"))]
+        let res = run_app(&mut terminal, &mut apps, &mut session, &mut TerminalEvents);
+        #[cfg(feature = "async")]
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .and_then(|runtime| {
+                runtime.block_on(async_loop::run_app(
+                    &mut terminal,
+                    &mut apps,
+                    &mut session,
+                    event::EventStream::new(),
+                ))
+            });
+        res
+    };
+    // A signal may mean the terminal is going away, so only quitting
+    // gets the outro
+    let res = match res {
+        Ok(()) if cli.ember_outro && !session.terminated.load(Ordering::Relaxed) => {
+            run_outro(&mut terminal, &mut apps, &mut session, &mut TerminalEvents)
+        }
+        res => res,
+    };
+    let res = end_on_disconnect(res);
+
+    disable_raw_mode()?;
+    if cli.pause_unfocused {
+        execute!(terminal.backend_mut(), DisableFocusChange)?;
+    }
+    if cli.mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    if alternate_screen {
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    } else {
+        execute!(terminal.backend_mut(), Clear(ClearType::All), MoveTo(0, 0))?;
+    }
+    terminal.show_cursor()?;
+
+    match res {
+        Ok(()) => {
+            // Written as a TOML comment so it can share stderr with the config
+            if cli.seed.is_none() {
+                eprintln!("# seed: {0} (rerun with --seed {0})", apps[0].seed());
+            }
+            let exit_config = cli.print_config_on_exit.then(|| apps[0].config.clone());
+            if let Some(config) = session.saved_config.or(exit_config) {
+                eprint!("{}", config.to_toml());
+            }
+        }
+        Err(err) => eprintln!("{err:?}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports an event ready on every poll, and fails every read.
+    #[cfg(not(feature = "async"))]
+    struct FailingEvents(fn() -> io::Error);
+
+    #[cfg(not(feature = "async"))]
+    impl EventSource for FailingEvents {
+        fn poll(&mut self, _timeout: Duration) -> io::Result<bool> {
+            Ok(true)
+        }
+
+        fn read(&mut self) -> io::Result<CrosstermEvent> {
+            Err((self.0)())
+        }
+    }
+
+    #[test]
+    fn disconnects_end_the_run() {
+        for kind in [
+            io::ErrorKind::BrokenPipe,
+            io::ErrorKind::UnexpectedEof,
+            io::ErrorKind::NotConnected,
+            io::ErrorKind::ConnectionReset,
+        ] {
+            assert!(is_disconnect(&io::Error::from(kind)), "{kind:?}");
+        }
+        assert!(!is_disconnect(&io::Error::from(
+            io::ErrorKind::PermissionDenied
+        )));
+        assert!(!is_disconnect(&io::Error::other("disk full")));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn only_the_terminals_eio_is_a_disconnect() {
+        let eio = || io::Error::from_raw_os_error(5);
+        assert!(!is_disconnect(&eio()));
+        assert!(is_disconnect(&hung_up(eio())));
+        // Other errors pass through as they are
+        let denied = hung_up(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(denied.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    #[cfg(not(feature = "async"))]
+    fn run_app_returns_when_the_events_fail() {
+        let run = |error: fn() -> io::Error| {
+            let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(40, 10)).unwrap();
+            let mut apps = vec![App::new(40, 10).with_seed(1)];
+            let mut session = Session::new(false, false, None);
+            end_on_disconnect(run_app(
+                &mut terminal,
+                &mut apps,
+                &mut session,
+                &mut FailingEvents(error),
+            ))
+        };
+        assert!(run(|| io::Error::from(io::ErrorKind::BrokenPipe)).is_ok());
+        assert!(run(|| io::Error::from(io::ErrorKind::UnexpectedEof)).is_ok());
+        assert!(run(|| io::Error::from(io::ErrorKind::ConnectionReset)).is_ok());
+        assert!(run(|| io::Error::other("unreadable")).is_err());
+    }
+
+    #[test]
+    fn each_pause_mode_stops_what_it_should() {
+        let press = |c| CrosstermEvent::Key(event::KeyEvent::from(KeyCode::Char(c)));
+        let burning = || {
+            let mut apps = vec![App::new(40, 20).with_seed(2)];
+            advance_apps(&mut apps, &Session::new(false, false, None), TICK_RATE * 20);
+            apps
+        };
+        let dt = TICK_RATE * 5;
+
+        // Running, the heat moves on and every frame is drawn
+        let mut apps = burning();
+        let mut session = Session::new(false, true, None);
+        let before = apps[0].grid().to_vec();
+        advance_apps(&mut apps, &session, dt);
+        assert_ne!(apps[0].grid(), &before[..]);
+        assert!(!session.paused() && session.should_draw() && session.should_draw());
+
+        // Paused, nothing is advanced but the frame is still drawn
+        handle_event(press(' '), &mut apps, &mut session);
+        assert!(session.paused() && session.should_draw());
+        handle_event(press(' '), &mut apps, &mut session);
+        assert!(!session.paused());
+
+        // Frozen, the clock runs and the glyphs are re-picked, but the heat
+        // stays put
+        handle_event(press('f'), &mut apps, &mut session);
+        let before = apps[0].grid().to_vec();
+        advance_apps(&mut apps, &session, dt);
+        assert!(!session.paused());
+        assert_eq!(apps[0].grid(), &before[..]);
+        handle_event(press('f'), &mut apps, &mut session);
+
+        // Hard frozen, everything stops and the frame is drawn once more
+        // for each key
+        handle_event(press('F'), &mut apps, &mut session);
+        assert!(session.paused());
+        assert!(session.should_draw());
+        assert!(!session.should_draw());
+        handle_event(press('a'), &mut apps, &mut session);
+        assert!(session.should_draw() && !session.should_draw());
+        handle_event(press('F'), &mut apps, &mut session);
+        assert!(!session.paused() && session.should_draw());
+
+        // Unfocused, it pauses only with --pause-unfocused
+        handle_event(CrosstermEvent::FocusLost, &mut apps, &mut session);
+        assert!(session.paused());
+        let mut session = Session::new(false, false, None);
+        handle_event(CrosstermEvent::FocusLost, &mut apps, &mut session);
+        assert!(!session.paused());
+    }
+
+    #[test]
+    fn clicks_paint_with_a_brush_sized_by_ctrl_scroll() {
+        let mouse = |kind, column, row, modifiers| {
+            CrosstermEvent::Mouse(event::MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers,
+            })
+        };
+        let mut apps = vec![App::new(40, 20), App::new(40, 20)];
+        let mut session = Session::new(false, false, None);
+        session.pane_areas = vec![Rect::new(1, 1, 40, 20), Rect::new(43, 1, 40, 20)];
+        let ctrl = event::KeyModifiers::CONTROL;
+        for _ in 0..2 {
+            handle_event(
+                mouse(MouseEventKind::ScrollUp, 0, 0, ctrl),
+                &mut apps,
+                &mut session,
+            );
+        }
+        assert_eq!(session.brush_radius, BRUSH_RADIUS + 2);
+        assert_eq!(apps[0].config.intensity, 0.5);
+
+        let click = MouseEventKind::Down(MouseButton::Left);
+        let none = event::KeyModifiers::NONE;
+        handle_event(mouse(click, 53, 6, none), &mut apps, &mut session);
+        // Only the pane under the pointer is lit, around the cell clicked
+        assert!(apps[0].grid().iter().flatten().all(|&heat| heat == 0));
+        let grid = apps[1].grid();
+        assert_eq!(grid[5][10], Heat::MAX);
+        assert!(grid[5][10 - BRUSH_RADIUS - 2] > 0 && grid[5][10 + BRUSH_RADIUS + 3] == 0);
+    }
+
+    #[test]
+    fn the_scroll_wheel_nudges_the_intensity() {
+        let scroll = |kind| {
+            CrosstermEvent::Mouse(event::MouseEvent {
+                kind,
+                column: 0,
+                row: 0,
+                modifiers: event::KeyModifiers::NONE,
+            })
+        };
+        let mut apps = vec![App::new(40, 10)];
+        let mut session = Session::new(false, false, None);
+        handle_event(scroll(MouseEventKind::ScrollUp), &mut apps, &mut session);
+        assert_eq!(apps[0].config.intensity, 0.5 + INTENSITY_STEP);
+        // Like the keys, the wheel stops at the ends of the range
+        for _ in 0..40 {
+            handle_event(scroll(MouseEventKind::ScrollDown), &mut apps, &mut session);
+        }
+        assert_eq!(apps[0].config.intensity, 0.0);
+        assert_eq!(apps[0].toast(), Some("intensity: 0.00"));
+    }
+}
@@ -0,0 +1,106 @@
+use crossterm::{
+    cursor::{Hide, Show},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    Frame, Terminal, TerminalOptions, Viewport, backend::CrosstermBackend, layout::Rect,
+};
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    path::PathBuf,
+};
+
+/// Extra terminals from `--output` that show the same frames as the main one,
+/// e.g. for signage. Frames are cut down to the size all of them share.
+/// Dropping it gives each terminal its normal screen back.
+#[derive(Default)]
+pub struct Mirrors {
+    // Each tty is kept alongside its terminal to read its size
+    terminals: Vec<(File, Terminal<CrosstermBackend<File>>)>,
+}
+
+impl Mirrors {
+    /// Opens each tty device and switches it to the alternate screen.
+    pub fn open(paths: &[PathBuf]) -> io::Result<Mirrors> {
+        let mut mirrors = Mirrors::default();
+        for path in paths {
+            let open_error = |err: io::Error| {
+                io::Error::new(
+                    err.kind(),
+                    format!("can't open '{}': {err}", path.display()),
+                )
+            };
+            let tty = OpenOptions::new()
+                .write(true)
+                .open(path)
+                .map_err(open_error)?;
+            let (columns, rows) = tty_size(&tty).map_err(open_error)?;
+            let mut output = tty.try_clone()?;
+            execute!(output, EnterAlternateScreen, Hide)?;
+            let options = TerminalOptions {
+                viewport: Viewport::Fixed(Rect::new(0, 0, columns, rows)),
+            };
+            let terminal = Terminal::with_options(CrosstermBackend::new(output), options)?;
+            mirrors.terminals.push((tty, terminal));
+        }
+        Ok(mirrors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terminals.is_empty()
+    }
+
+    /// The columns and rows every mirror fits, read afresh so resized
+    /// terminals are picked up.
+    pub fn size(&self) -> io::Result<Option<(u16, u16)>> {
+        self.terminals.iter().try_fold(None, |smallest, (tty, _)| {
+            let (columns, rows) = tty_size(tty)?;
+            Ok(Some(match smallest {
+                Some((min_columns, min_rows)) => (columns.min(min_columns), rows.min(min_rows)),
+                None => (columns, rows),
+            }))
+        })
+    }
+
+    /// Draws a frame of `columns` by `rows` cells to every mirror.
+    pub fn draw(
+        &mut self,
+        (columns, rows): (u16, u16),
+        mut render: impl FnMut(&mut Frame),
+    ) -> io::Result<()> {
+        let area = Rect::new(0, 0, columns, rows);
+        for (_, terminal) in &mut self.terminals {
+            if terminal.get_frame().area() != area {
+                terminal.resize(area)?;
+            }
+            terminal.draw(&mut render)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Mirrors {
+    fn drop(&mut self) {
+        for (_, terminal) in &mut self.terminals {
+            // Nothing more can be done about a terminal that has gone away
+            let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen, Show);
+        }
+    }
+}
+
+/// Reads the columns and rows of the terminal behind a tty device.
+#[cfg(unix)]
+fn tty_size(tty: &File) -> io::Result<(u16, u16)> {
+    let size = rustix::termios::tcgetwinsize(tty)?;
+    Ok((size.ws_col, size.ws_row))
+}
+
+#[cfg(not(unix))]
+fn tty_size(_tty: &File) -> io::Result<(u16, u16)> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "mirroring to other terminals is only supported on Unix",
+    ))
+}
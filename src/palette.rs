@@ -0,0 +1,722 @@
+use clap::ValueEnum;
+use ratatui::style::Color;
+use std::{env, fmt, fs, io, path::Path};
+
+/// The built-in fire palette, ordered from the coldest band to the hottest.
+pub const FIRE: [Color; 11] = [
+    Color::Black,              // For very low/no heat (background)
+    Color::Rgb(175, 0, 0),     // Deep red, subtle embers
+    Color::Rgb(255, 0, 0),     // Red
+    Color::Rgb(255, 150, 50),  // Orange-Red
+    Color::Rgb(255, 175, 75),  // Dark Orange
+    Color::Rgb(255, 200, 100), // Orange
+    Color::Yellow,             // Yellow
+    Color::Rgb(255, 255, 150), // Light Yellow
+    Color::White,              // White, very hot core
+    Color::Rgb(255, 255, 200), // Brighter white
+    Color::Rgb(255, 255, 250), // Almost pure white for brightest parts
+];
+
+/// A palette limited to the basic ANSI colors, for a retro look that works on
+/// any color terminal, in three bands.
+///
+/// ```
+/// use fire_in_the_term::{App, config::FireConfig, palette::RETRO};
+/// use ratatui::style::Color;
+///
+/// let config = FireConfig { palette: RETRO.to_vec(), ..FireConfig::default() };
+/// let app = App::new(40, 20).with_config(config);
+/// assert_eq!(app.color_for_heat(0), Color::Red);
+/// assert_eq!(app.color_for_heat(200), Color::Yellow);
+/// assert_eq!(app.color_for_heat(255), Color::White);
+/// ```
+pub const RETRO: [Color; 3] = [Color::Red, Color::Yellow, Color::White];
+
+/// A cold blue flame.
+pub const ICE: [Color; 8] = [
+    Color::Black,
+    Color::Rgb(0, 20, 80),
+    Color::Rgb(0, 50, 160),
+    Color::Rgb(20, 100, 220),
+    Color::Rgb(60, 160, 255),
+    Color::Rgb(130, 210, 255),
+    Color::Rgb(200, 240, 255),
+    Color::White,
+];
+
+/// The blue base of a gas flame, from its dim edges to its bright core.
+pub const GAS_BLUE: [Color; 4] = [
+    Color::Rgb(20, 40, 180),
+    Color::Rgb(30, 90, 230),
+    Color::Rgb(60, 150, 255),
+    Color::Rgb(140, 200, 255),
+];
+
+/// A sickly green flame.
+pub const TOXIC: [Color; 8] = [
+    Color::Black,
+    Color::Rgb(10, 50, 0),
+    Color::Rgb(30, 110, 0),
+    Color::Rgb(60, 170, 0),
+    Color::Rgb(120, 220, 20),
+    Color::Rgb(190, 250, 60),
+    Color::Rgb(230, 255, 160),
+    Color::Rgb(250, 255, 230),
+];
+
+/// The built-in palettes, in the order they are cycled through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+    Fire,
+    Ice,
+    Toxic,
+    Retro,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [Preset::Fire, Preset::Ice, Preset::Toxic, Preset::Retro];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Fire => "fire",
+            Preset::Ice => "ice",
+            Preset::Toxic => "toxic",
+            Preset::Retro => "retro",
+        }
+    }
+
+    pub fn colors(self) -> &'static [Color] {
+        match self {
+            Preset::Fire => &FIRE,
+            Preset::Ice => &ICE,
+            Preset::Toxic => &TOXIC,
+            Preset::Retro => &RETRO,
+        }
+    }
+
+    /// Finds the preset with exactly these colors.
+    pub fn find(colors: &[Color]) -> Option<Preset> {
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.colors() == colors)
+    }
+
+    /// Returns the preset after this one, wrapping around.
+    pub fn next(self) -> Preset {
+        let index = Preset::ALL
+            .iter()
+            .position(|&preset| preset == self)
+            .unwrap_or(0);
+        Preset::ALL[(index + 1) % Preset::ALL.len()]
+    }
+}
+
+/// How many colors the terminal can show. Palette colors are reduced to the
+/// nearest one available.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Any 24-bit color
+    #[default]
+    Truecolor,
+    /// The xterm 256-color palette
+    Ansi256,
+    /// The 16 basic ANSI colors
+    Ansi16,
+}
+
+impl ColorMode {
+    /// Guesses what the terminal supports from the `COLORTERM` and `TERM`
+    /// environment variables.
+    pub fn detect() -> ColorMode {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        if matches!(colorterm.as_str(), "truecolor" | "24bit")
+            || term.ends_with("-direct")
+            || term.ends_with("-truecolor")
+        {
+            ColorMode::Truecolor
+        } else if term.contains("256color") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+
+    /// Replaces a color with the closest one the mode can show.
+    pub fn reduce(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorMode::Truecolor, _) => color,
+            (ColorMode::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256(r, g, b)),
+            (ColorMode::Ansi256, _) => color,
+            (ColorMode::Ansi16, Color::Rgb(..) | Color::Indexed(_)) => {
+                let distance =
+                    |candidate: &Color| color_distance(to_rgb(*candidate), to_rgb(color));
+                ANSI_16.into_iter().min_by_key(distance).unwrap_or(color)
+            }
+            (ColorMode::Ansi16, _) => color,
+        }
+    }
+}
+
+/// The basic ANSI colors, in the order of their color indices.
+const ANSI_16: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Channel values of the 6x6x6 color cube in the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Finds the closest entry of the color cube or the gray ramp, leaving out
+/// the first 16 colors since terminals theme them differently.
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |channel: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(channel))
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    // Grays run from 8 to 238 in steps of 10
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_step;
+
+    if color_distance((gray, gray, gray), (r, g, b)) < color_distance(cube, (r, g, b)) {
+        232 + gray_step
+    } else {
+        cube_index as u8
+    }
+}
+
+fn color_distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let square = |a: u8, b: u8| (a.abs_diff(b) as u32).pow(2);
+    square(r1, r2) + square(g1, g2) + square(b1, b2)
+}
+
+#[derive(Debug)]
+pub enum PaletteError {
+    Io(io::Error),
+    Malformed { line: usize, reason: String },
+    TooFewColors(usize),
+    NotDarkToBright,
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::Io(err) => write!(f, "{err}"),
+            PaletteError::Malformed { line, reason } => write!(f, "line {line}: {reason}"),
+            PaletteError::TooFewColors(count) => {
+                write!(f, "expected at least 2 colors, found {count}")
+            }
+            PaletteError::NotDarkToBright => {
+                write!(f, "colors must be ordered from dark to bright")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {}
+
+impl From<io::Error> for PaletteError {
+    fn from(err: io::Error) -> Self {
+        PaletteError::Io(err)
+    }
+}
+
+/// Reads a palette from a GIMP `.gpl` file or a list of `#RRGGBB` lines.
+pub fn load_palette_file(path: &Path) -> Result<Vec<Color>, PaletteError> {
+    parse_palette(&fs::read_to_string(path)?)
+}
+
+/// Writes a palette as one `#RRGGBB` line per color, in the format
+/// [`parse_palette`] reads back. Named and indexed colors are written as the
+/// RGB values they are drawn with.
+///
+/// ```
+/// use fire_in_the_term::palette::{self, format_palette, parse_palette};
+/// use ratatui::style::Color;
+///
+/// assert_eq!(format_palette(&palette::RETRO[..2]), "#CD0000\n#CDCD00\n");
+/// // Reads back as it was written
+/// let colors = palette::gradient(Color::Rgb(51, 0, 0), Color::Rgb(255, 255, 153), 8);
+/// assert_eq!(parse_palette(&format_palette(&colors)).unwrap(), colors);
+/// ```
+pub fn format_palette(colors: &[Color]) -> String {
+    colors
+        .iter()
+        .map(|&color| {
+            let (r, g, b) = to_rgb(color);
+            format!("#{r:02X}{g:02X}{b:02X}\n")
+        })
+        .collect()
+}
+
+/// Draws the palette as a heat gradient, coldest on the left, once in each
+/// color mode and once in shades of gray, as lines of `width` columns
+/// colored with SGR codes. Shows what each `--colors` mode makes of the
+/// palette, and how its brightness climbs with the hue left out.
+///
+/// ```
+/// use fire_in_the_term::palette::{self, preview};
+///
+/// let preview = preview(&palette::FIRE, 40);
+/// assert_eq!(preview.lines().count(), 4);
+/// assert!(preview.starts_with("truecolor "));
+/// ```
+pub fn preview(colors: &[Color], width: usize) -> String {
+    const LABEL_WIDTH: usize = 10;
+    let bar_width = width.saturating_sub(LABEL_WIDTH).max(1);
+    let modes = ColorMode::value_variants().iter().map(|&mode| {
+        let name = mode
+            .to_possible_value()
+            .map(|value| value.get_name().to_string());
+        (name.unwrap_or_default(), Some(mode))
+    });
+    let mut output = String::new();
+    for (name, mode) in modes.chain([("grayscale".to_string(), None)]) {
+        output.push_str(&format!("{name:<LABEL_WIDTH$}"));
+        let mut last_color = None;
+        for column in 0..bar_width {
+            let heat = column as f32 / (bar_width - 1).max(1) as f32;
+            let index = ((heat * (colors.len() - 1) as f32) as usize).min(colors.len() - 1);
+            let color = match mode {
+                Some(mode) => mode.reduce(colors[index]),
+                None => {
+                    let gray = luminance(colors[index]).round() as u8;
+                    Color::Rgb(gray, gray, gray)
+                }
+            };
+            if last_color != Some(color) {
+                output.push_str(&format!("\x1b[{}m", sgr_background(color)));
+                last_color = Some(color);
+            }
+            output.push(' ');
+        }
+        output.push_str("\x1b[0m\n");
+    }
+    output
+}
+
+/// Parses palette contents. Files starting with a `GIMP Palette` header are
+/// read as `.gpl`; anything else is treated as one hex color per line.
+pub fn parse_palette(contents: &str) -> Result<Vec<Color>, PaletteError> {
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .peekable();
+
+    let is_gpl = lines
+        .peek()
+        .is_some_and(|(_, line)| *line == "GIMP Palette");
+    if is_gpl {
+        lines.next();
+    }
+
+    let mut colors = Vec::new();
+    for (line_number, line) in lines {
+        let color = if is_gpl {
+            // Header fields and comments can appear anywhere before the colors
+            if line.starts_with('#') || line.starts_with("Name:") || line.starts_with("Columns:") {
+                continue;
+            }
+            parse_gpl_line(line)
+        } else {
+            parse_hex(line)
+        };
+        colors.push(color.map_err(|reason| PaletteError::Malformed {
+            line: line_number,
+            reason,
+        })?);
+    }
+
+    validate(&colors)?;
+    Ok(colors)
+}
+
+/// Checks that a palette has at least two colors going from dark to bright.
+pub fn validate(colors: &[Color]) -> Result<(), PaletteError> {
+    if colors.len() < 2 {
+        return Err(PaletteError::TooFewColors(colors.len()));
+    }
+    if luminance(colors[0]) >= luminance(colors[colors.len() - 1]) {
+        return Err(PaletteError::NotDarkToBright);
+    }
+    Ok(())
+}
+
+fn parse_gpl_line(line: &str) -> Result<Color, String> {
+    // A color line is "R G B" optionally followed by a name
+    let mut channels = line.split_whitespace().take(3).map(|channel| {
+        channel
+            .parse::<u8>()
+            .map_err(|_| format!("invalid color channel '{channel}', expected 0-255"))
+    });
+    match (channels.next(), channels.next(), channels.next()) {
+        (Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r?, g?, b?)),
+        _ => Err(format!("expected 'R G B [name]', found '{line}'")),
+    }
+}
+
+fn parse_hex(line: &str) -> Result<Color, String> {
+    let invalid = || format!("expected a #RRGGBB color, found '{line}'");
+    let hex = line.strip_prefix('#').ok_or_else(invalid)?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let channel = |range| u8::from_str_radix(&hex[range], 16).map_err(|_| invalid());
+    Ok(Color::Rgb(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
+
+/// Perceptually uniform colormaps from matplotlib, for `--colormap`. Their
+/// lightness rises evenly from one end to the other, so equal steps of heat
+/// look equally far apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Colormap {
+    /// Black through purple and red to pale yellow, like glowing embers
+    Inferno,
+    /// Black through purple and pink to cream
+    Magma,
+    /// Blue through purple and orange to yellow
+    Plasma,
+    /// Purple through blue and green to yellow
+    Viridis,
+}
+
+impl Colormap {
+    /// Colors at evenly spaced points along the colormap, from its dark end.
+    fn control_points(self) -> [Color; 10] {
+        let hex = |rgb: u32| Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8);
+        let points = match self {
+            Colormap::Inferno => [
+                0x000004, 0x1B0C42, 0x4B0C6B, 0x781C6D, 0xA52C60, 0xCF4446, 0xED6925, 0xFB9A06,
+                0xF7D03C, 0xFCFFA4,
+            ],
+            Colormap::Magma => [
+                0x000004, 0x180F3E, 0x451077, 0x721F81, 0x9F2F7F, 0xCD4071, 0xF1605D, 0xFD9567,
+                0xFEC98D, 0xFCFDBF,
+            ],
+            Colormap::Plasma => [
+                0x0D0887, 0x47039F, 0x7301A8, 0x9C179E, 0xBD3786, 0xD8576B, 0xED7953, 0xFA9E3B,
+                0xFDC926, 0xF0F921,
+            ],
+            Colormap::Viridis => [
+                0x440154, 0x482878, 0x3E4A89, 0x31688E, 0x26828E, 0x1F9E89, 0x35B779, 0x6DCD59,
+                0xB4DE2C, 0xFDE725,
+            ],
+        };
+        points.map(hex)
+    }
+
+    /// Builds a palette of `bands` colors read off the colormap at even
+    /// steps, its dark end for the coldest heat and its bright end for the
+    /// hottest, blending between the control points in between.
+    ///
+    /// ```
+    /// use fire_in_the_term::palette::Colormap;
+    /// use ratatui::style::Color;
+    ///
+    /// let colors = Colormap::Inferno.colors(16);
+    /// assert_eq!(colors.len(), 16);
+    /// assert_eq!(colors[0], Color::Rgb(0, 0, 4));
+    /// assert_eq!(colors[15], Color::Rgb(252, 255, 164));
+    /// ```
+    pub fn colors(self, bands: usize) -> Vec<Color> {
+        let points = self.control_points();
+        let last_point = (points.len() - 1) as f32;
+        (0..bands)
+            .map(|band| {
+                let position = band as f32 / (bands.max(2) - 1) as f32 * last_point;
+                let lower = position as usize;
+                let upper = (lower + 1).min(points.len() - 1);
+                blend(points[lower], points[upper], position.fract())
+            })
+            .collect()
+    }
+}
+
+/// Number of colors in a palette built by [`blackbody`].
+const BLACKBODY_BANDS: usize = 32;
+
+/// Builds a palette of blackbody colors from `min_kelvin` for the coldest heat
+/// to `max_kelvin` for the hottest. Colder bands are also dimmer, starting from
+/// black.
+pub fn blackbody(min_kelvin: f32, max_kelvin: f32) -> Vec<Color> {
+    (0..BLACKBODY_BANDS)
+        .map(|band| {
+            let t = band as f32 / (BLACKBODY_BANDS - 1) as f32;
+            let color = kelvin_to_rgb(min_kelvin + (max_kelvin - min_kelvin) * t);
+            blend(Color::Black, color, t.sqrt())
+        })
+        .collect()
+}
+
+/// Builds a palette of `bands` colors from `from` for the coldest heat to `to`
+/// for the hottest, interpolating in HSL so the colors in between keep their
+/// saturation instead of turning muddy. The hue takes the short way around
+/// the color wheel.
+///
+/// ```
+/// use fire_in_the_term::palette::gradient;
+/// use ratatui::style::Color;
+///
+/// let colors = gradient(Color::Rgb(51, 0, 0), Color::Rgb(255, 255, 153), 5);
+/// assert_eq!(colors.len(), 5);
+/// assert_eq!(colors[0], Color::Rgb(51, 0, 0));
+/// assert_eq!(colors[4], Color::Rgb(255, 255, 153));
+/// ```
+pub fn gradient(from: Color, to: Color, bands: usize) -> Vec<Color> {
+    let (from_hue, from_saturation, from_lightness) = rgb_to_hsl(to_rgb(from));
+    let (to_hue, to_saturation, to_lightness) = rgb_to_hsl(to_rgb(to));
+    // Grays have no hue of their own, so they take the other end's
+    let from_hue = if from_saturation == 0.0 {
+        to_hue
+    } else {
+        from_hue
+    };
+    let to_hue = if to_saturation == 0.0 {
+        from_hue
+    } else {
+        to_hue
+    };
+    let hue_change = (to_hue - from_hue + 180.0).rem_euclid(360.0) - 180.0;
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    (0..bands)
+        .map(|band| {
+            let t = band as f32 / (bands.max(2) - 1) as f32;
+            hsl_to_rgb(
+                (from_hue + hue_change * t).rem_euclid(360.0),
+                lerp(from_saturation, to_saturation, t),
+                lerp(from_lightness, to_lightness, t),
+            )
+        })
+        .collect()
+}
+
+/// Palette for a time of day, in hours since midnight: the warm fire palette
+/// through the evening and night, turning to the cool, bright ice palette
+/// over the morning, holding it into the afternoon and turning back towards
+/// the evening.
+///
+/// ```
+/// use fire_in_the_term::palette::{self, time_of_day};
+///
+/// assert_eq!(time_of_day(22.0), palette::FIRE);
+/// assert_eq!(time_of_day(13.0), palette::ICE);
+/// // Halfway through the morning it is somewhere in between
+/// assert_eq!(time_of_day(9.5).len(), palette::FIRE.len());
+/// ```
+pub fn time_of_day(hour: f32) -> Vec<Color> {
+    let ease = |t: f32| t * t * (3.0 - 2.0 * t);
+    let coolness = match hour.rem_euclid(24.0) {
+        hour if (7.0..12.0).contains(&hour) => ease((hour - 7.0) / 5.0),
+        hour if (12.0..15.0).contains(&hour) => 1.0,
+        hour if (15.0..19.0).contains(&hour) => 1.0 - ease((hour - 15.0) / 4.0),
+        _ => 0.0,
+    };
+    if coolness == 0.0 {
+        return FIRE.to_vec();
+    }
+    if coolness == 1.0 {
+        return ICE.to_vec();
+    }
+    // Ice has fewer bands, so it is read off at each fire band's position
+    let last_band = (FIRE.len() - 1) as f32;
+    (FIRE.iter().enumerate())
+        .map(|(band, &fire)| {
+            let position = band as f32 / last_band * (ICE.len() - 1) as f32;
+            let lower = position as usize;
+            let upper = (lower + 1).min(ICE.len() - 1);
+            let ice = blend(ICE[lower], ICE[upper], position.fract());
+            blend(fire, ice, coolness)
+        })
+        .collect()
+}
+
+/// Approximates the color of a blackbody at a temperature in Kelvin, using
+/// Tanner Helland's curve fit. Accurate from about 1000K to 40000K.
+pub fn kelvin_to_rgb(kelvin: f32) -> Color {
+    let temperature = kelvin / 100.0;
+    let red = if temperature <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (temperature - 60.0).powf(-0.13320476)
+    };
+    let green = if temperature <= 66.0 {
+        99.4708 * temperature.ln() - 161.11957
+    } else {
+        288.12216 * (temperature - 60.0).powf(-0.075514846)
+    };
+    let blue = if temperature >= 66.0 {
+        255.0
+    } else if temperature <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (temperature - 10.0).ln() - 305.0448
+    };
+    let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+    Color::Rgb(channel(red), channel(green), channel(blue))
+}
+
+/// Converts any palette color to RGB, resolving named and indexed colors to
+/// their usual xterm values.
+pub fn to_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::Gray => (229, 229, 229),
+        Color::DarkGray => (127, 127, 127),
+        Color::LightRed => (255, 0, 0),
+        Color::LightGreen => (0, 255, 0),
+        Color::LightYellow => (255, 255, 0),
+        Color::LightBlue => (92, 92, 255),
+        Color::LightMagenta => (255, 0, 255),
+        Color::LightCyan => (0, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Indexed(index @ 0..16) => to_rgb(ANSI_16[index as usize]),
+        Color::Indexed(index @ 16..232) => {
+            let cube = index - 16;
+            let level = |i: u8| CUBE_LEVELS[i as usize];
+            (level(cube / 36), level(cube / 6 % 6), level(cube % 6))
+        }
+        Color::Indexed(index) => {
+            let gray = 8 + 10 * (index - 232);
+            (gray, gray, gray)
+        }
+        _ => (0, 0, 0),
+    }
+}
+
+/// Linearly interpolates between two colors in RGB, with `t` from 0 (`from`)
+/// to 1 (`to`).
+pub fn blend(from: Color, to: Color, t: f32) -> Color {
+    let (r1, g1, b1) = to_rgb(from);
+    let (r2, g2, b2) = to_rgb(to);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::Rgb(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2))
+}
+
+/// Scales a color's contrast around mid gray, then its brightness, clamping
+/// each channel to 0-255.
+pub fn adjust(color: Color, brightness: f32, contrast: f32) -> Color {
+    let (r, g, b) = to_rgb(color);
+    let channel = |value: u8| {
+        let adjusted = ((value as f32 - 127.5) * contrast + 127.5) * brightness;
+        adjusted.round().clamp(0.0, 255.0) as u8
+    };
+    Color::Rgb(channel(r), channel(g), channel(b))
+}
+
+/// Rotates a color's hue around the color wheel, keeping its saturation and
+/// lightness.
+pub fn rotate_hue(color: Color, degrees: f32) -> Color {
+    let (hue, saturation, lightness) = rgb_to_hsl(to_rgb(color));
+    hsl_to_rgb((hue + degrees).rem_euclid(360.0), saturation, lightness)
+}
+
+/// Converts RGB to hue (degrees), saturation and lightness (both 0 to 1).
+fn rgb_to_hsl((r, g, b): (u8, u8, u8)) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> Color {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |value: f32| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::Rgb(channel(r), channel(g), channel(b))
+}
+
+/// Returns the SGR parameters that set `color` as the foreground, e.g.
+/// `38;2;255;0;0`.
+pub fn sgr_foreground(color: Color) -> String {
+    sgr_color(color, 30)
+}
+
+/// Returns the SGR parameters that set `color` as the background, e.g.
+/// `48;2;255;0;0`.
+pub fn sgr_background(color: Color) -> String {
+    sgr_color(color, 40)
+}
+
+// `base` is 30 for foreground and 40 for background codes
+fn sgr_color(color: Color, base: u8) -> String {
+    let offset = match color {
+        Color::Reset => 9,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 60,
+        Color::LightRed => 61,
+        Color::LightGreen => 62,
+        Color::LightYellow => 63,
+        Color::LightBlue => 64,
+        Color::LightMagenta => 65,
+        Color::LightCyan => 66,
+        Color::White => 67,
+        Color::Indexed(index) => return format!("{};5;{index}", base + 8),
+        Color::Rgb(r, g, b) => return format!("{};2;{r};{g};{b}", base + 8),
+    };
+    (base + offset).to_string()
+}
+
+fn luminance(color: Color) -> f32 {
+    let (r, g, b) = to_rgb(color);
+    0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32
+}
@@ -0,0 +1,38 @@
+use fire_in_the_term::{App, palette};
+use ratatui::style::Color;
+
+/// Pixels drawn for each terminal cell, roughly matching a cell's aspect ratio.
+pub const CELL_WIDTH: usize = 4;
+pub const CELL_HEIGHT: usize = 8;
+
+/// Size in pixels of the image [`render_pixels`] paints for `app`.
+pub fn image_size(app: &App) -> (usize, usize) {
+    let (display_width, display_height) = app.display_size();
+    (display_width * CELL_WIDTH, display_height * CELL_HEIGHT)
+}
+
+/// Paints the current frame into an RGB buffer, filling each cell with its
+/// glyph's color, or the background where the glyph is blank. Glyphs drawn in
+/// the terminal's foreground become white.
+pub fn render_pixels(app: &App, pixel_width: usize, pixel_height: usize) -> Vec<u8> {
+    let background = app.config.background().unwrap_or(Color::Black);
+    let mut pixels = vec![0; pixel_width * pixel_height * 3];
+
+    for (y, line) in app.render_fire().lines.iter().enumerate() {
+        for (x, span) in line.spans.iter().enumerate() {
+            let color = match span.style.fg {
+                _ if span.content.trim().is_empty() => background,
+                Some(color) => color,
+                None => Color::White,
+            };
+            let (r, g, b) = palette::to_rgb(color);
+            for row in y * CELL_HEIGHT..(y + 1) * CELL_HEIGHT {
+                let start = (row * pixel_width + x * CELL_WIDTH) * 3;
+                for pixel in pixels[start..start + CELL_WIDTH * 3].chunks_exact_mut(3) {
+                    pixel.copy_from_slice(&[r, g, b]);
+                }
+            }
+        }
+    }
+    pixels
+}
@@ -0,0 +1,123 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// The built-in sets of glyphs used to draw the flame, from cold to hot.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CharRamp {
+    /// Several varied ASCII glyphs per heat band
+    #[default]
+    Classic,
+    /// One ASCII glyph per band: ` .:-=+*#`
+    Simple,
+    /// Shaded blocks: ` ░▒▓█`
+    Blocks,
+    /// The classic glyphs less the ones fonts disagree on, for fonts that draw
+    /// some of them oddly. Toggled with `A` rather than cycled through, so it
+    /// isn't in `ALL`
+    Safe,
+}
+
+/// How each cell's glyph is picked from the variants of its heat band, when
+/// glyphs don't follow the heat.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum GlyphVariety {
+    /// A fresh random pick for every cell on every frame
+    #[default]
+    Random,
+    /// A cheap hash of each cell's position, reshuffled every frame. Looks
+    /// the same but takes a single random number a frame
+    Hashed,
+    /// Always the first variant of each band, with no randomness at all
+    Fixed,
+}
+
+impl CharRamp {
+    pub const ALL: [CharRamp; 3] = [CharRamp::Classic, CharRamp::Simple, CharRamp::Blocks];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CharRamp::Classic => "classic",
+            CharRamp::Simple => "simple",
+            CharRamp::Blocks => "blocks",
+            CharRamp::Safe => "safe",
+        }
+    }
+
+    /// Returns the ramp after this one, wrapping around.
+    pub fn next(self) -> CharRamp {
+        let index = CharRamp::ALL
+            .iter()
+            .position(|&ramp| ramp == self)
+            .unwrap_or(0);
+        CharRamp::ALL[(index + 1) % CharRamp::ALL.len()]
+    }
+
+    /// Builds the lookup table of glyph variants for each heat band.
+    pub fn char_map(self) -> Vec<Vec<char>> {
+        match self {
+            CharRamp::Classic => vec![
+                vec![' '],
+                vec!['.', '\'', '`', ','],
+                vec!['~', '-', ';', ':'],
+                vec!['"', ';', ':', '^'],
+                vec!['!', '?', '=', '"'],
+                vec!['(', ')', '|', '!'],
+                vec!['[', ']', '\\', '/'],
+                vec!['{', '}', 'I', 'V'],
+                vec!['o', 'T', 'O', 'V'],
+                vec!['H', 'A', '0', '*'],
+                vec!['M', 'W', '%', 'X'],
+                vec!['#', '$', '@', '&'],
+            ],
+            // Dropped from the classic glyphs: '`' and '^', which many fonts
+            // draw as faint specks at the top of the cell; '~', which some
+            // draw raised or as an overline; '|', which some draw broken; and
+            // '\', which Japanese and Korean fonts draw as a yen or won sign
+            CharRamp::Safe => vec![
+                vec![' '],
+                vec!['.', '\'', ','],
+                vec!['-', ';', ':'],
+                vec!['"', ';', ':'],
+                vec!['!', '?', '=', '"'],
+                vec!['(', ')', '!'],
+                vec!['[', ']', '/'],
+                vec!['{', '}', 'I', 'V'],
+                vec!['o', 'T', 'O', 'V'],
+                vec!['H', 'A', '0', '*'],
+                vec!['M', 'W', '%', 'X'],
+                vec!['#', '$', '@', '&'],
+            ],
+            CharRamp::Simple => " .:-=+*#".chars().map(|c| vec![c]).collect(),
+            CharRamp::Blocks => " ░▒▓█".chars().map(|c| vec![c]).collect(),
+        }
+    }
+
+    /// Like [`CharRamp::char_map`], resampled to `bands` heat bands so each
+    /// band lines up with a palette color of the same count. The coldest and
+    /// hottest bands are kept; the ones between take the nearest band.
+    ///
+    /// ```
+    /// use fire_in_the_term::ramp::CharRamp;
+    ///
+    /// // Three bands, for a three-color palette
+    /// let char_map = CharRamp::Classic.char_map_with_bands(3);
+    /// assert_eq!(char_map.len(), 3);
+    /// assert_eq!(char_map[0], [' ']);
+    /// assert_eq!(char_map[2], ['#', '$', '@', '&']);
+    /// ```
+    pub fn char_map_with_bands(self, bands: usize) -> Vec<Vec<char>> {
+        let char_map = self.char_map();
+        if bands == char_map.len() || bands < 2 {
+            return char_map;
+        }
+        let last_band = (char_map.len() - 1) as f32;
+        (0..bands)
+            .map(|band| {
+                let source = (band as f32 / (bands - 1) as f32 * last_band).round() as usize;
+                char_map[source].clone()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,93 @@
+use fire_in_the_term::{config::FireConfig, keys::Action};
+use std::{
+    collections::VecDeque,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// A change made by a `--script`.
+pub enum Cue {
+    /// Does what the action's key does.
+    Action(Action),
+    /// Changes a config setting, e.g. `wind` to `0.5`.
+    Set(String, toml::Value),
+}
+
+/// A timeline of changes from `--script`, one per line as `<seconds> <action>`
+/// or `<seconds> <setting> <value>`, e.g. `5 palette_next` or `10 wind 0.5`.
+/// Values are written as in a config file, though words can be left
+/// unquoted. Blank lines and lines starting with `#` are skipped.
+pub struct Script {
+    // Cues yet to come, soonest first, and when the script started
+    cues: VecDeque<(Duration, Cue)>,
+    started: Instant,
+}
+
+impl Script {
+    /// Reads a script, checking every line so mistakes show up before the
+    /// fire starts rather than midway through.
+    pub fn load(path: &Path) -> Result<Script, String> {
+        let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let mut cues = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cue = parse_line(line).map_err(|reason| format!("line {}: {reason}", index + 1))?;
+            cues.push(cue);
+        }
+        // Stable, so cues at the same time keep their order
+        cues.sort_by_key(|(time, _)| *time);
+        Ok(Script {
+            cues: cues.into(),
+            started: Instant::now(),
+        })
+    }
+
+    /// Takes the cues that have come due, in order.
+    pub fn due(&mut self) -> Vec<Cue> {
+        let elapsed = self.started.elapsed();
+        let mut due = Vec::new();
+        while self.cues.front().is_some_and(|(time, _)| *time <= elapsed) {
+            due.extend(self.cues.pop_front().map(|(_, cue)| cue));
+        }
+        due
+    }
+}
+
+fn parse_line(line: &str) -> Result<(Duration, Cue), String> {
+    let mut fields = line.splitn(3, char::is_whitespace);
+    let (time, name) = match (fields.next(), fields.next()) {
+        (Some(time), Some(name)) => (time, name),
+        _ => return Err("expected '<seconds> <action>' or '<seconds> <setting> <value>'".into()),
+    };
+    let time = (time.parse().ok())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("'{time}' is not a number of seconds"))?;
+
+    let cue = match fields.next().map(str::trim) {
+        Some(value) => {
+            let value = parse_value(value);
+            // Tried on the defaults so unknown settings and bad values are caught
+            FireConfig::default()
+                .set(name, value.clone())
+                .map_err(|err| format!("can't set {name}: {err}"))?;
+            Cue::Set(name.to_string(), value)
+        }
+        None => match toml::Value::String(name.to_string()).try_into() {
+            Ok(action) => Cue::Action(action),
+            Err(_) => return Err(format!("unknown action '{name}'")),
+        },
+    };
+    Ok((time, cue))
+}
+
+/// Reads a value as written in a config file, or else as a bare word.
+fn parse_value(value: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
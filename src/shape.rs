@@ -0,0 +1,32 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Silhouettes the flame can be confined to. Cells outside the shape never
+/// hold any heat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlameShape {
+    /// Narrows evenly from the full width at the bottom to a point at the top
+    Triangle,
+    /// Bulges out above the embers and tapers to a point at the top
+    Teardrop,
+    /// A straight column half the width of the screen
+    Column,
+}
+
+impl FlameShape {
+    /// Whether the cell at `(x, y)` of a `width` by `height` grid, with row 0
+    /// at the top, is inside the shape.
+    pub fn contains(self, x: usize, y: usize, width: usize, height: usize) -> bool {
+        // Distance from the center line, from 0 to 1 at the edges, and
+        // height above the bottom, from 0 to 1 at the top
+        let across = ((x as f32 + 0.5) / width as f32 * 2.0 - 1.0).abs();
+        let up = 1.0 - (y as f32 + 0.5) / height as f32;
+        let half_width = match self {
+            FlameShape::Triangle => 1.0 - up,
+            FlameShape::Teardrop => 2.2 * (1.0 - up) * (up + 0.05).sqrt(),
+            FlameShape::Column => 0.5,
+        };
+        across <= half_width
+    }
+}
@@ -0,0 +1,79 @@
+use crate::palette;
+use ratatui::style::Color;
+use std::fmt::Write;
+
+/// Sixel rows are six pixels tall.
+const BAND_HEIGHT: usize = 6;
+
+/// Encodes a grid of indices into `colors` as a sixel image, drawing each cell
+/// as a `cell_width` by `cell_height` block of pixels.
+pub fn grid_to_sixel(
+    grid: &[Vec<usize>],
+    colors: &[Color],
+    cell_width: usize,
+    cell_height: usize,
+) -> String {
+    let columns = grid.first().map_or(0, Vec::len);
+    let (width, height) = (columns * cell_width, grid.len() * cell_height);
+    let mut sixel = String::new();
+
+    // Start the image with square pixels and declare its size and colors
+    write!(sixel, "\x1bP0;1;0q\"1;1;{width};{height}").unwrap();
+    for (index, &color) in colors.iter().enumerate() {
+        let (r, g, b) = palette::to_rgb(color);
+        let percent = |channel: u8| channel as u32 * 100 / 255;
+        write!(
+            sixel,
+            "#{index};2;{};{};{}",
+            percent(r),
+            percent(g),
+            percent(b)
+        )
+        .unwrap();
+    }
+
+    for band_top in (0..height).step_by(BAND_HEIGHT) {
+        let rows = band_top..(band_top + BAND_HEIGHT).min(height);
+        // Every pixel column of a cell is the same, so work on cell columns
+        let mut masks = vec![vec![0u8; columns]; colors.len()];
+        for (bit, y) in rows.enumerate() {
+            for (x, &index) in grid[y / cell_height].iter().enumerate() {
+                masks[index][x] |= 1 << bit;
+            }
+        }
+
+        let mut first = true;
+        for (index, mask) in masks.iter().enumerate() {
+            if mask.iter().all(|&bits| bits == 0) {
+                continue;
+            }
+            // `$` returns to the start of the band to overlay the next color
+            if !first {
+                sixel.push('$');
+            }
+            first = false;
+            write!(sixel, "#{index}").unwrap();
+            push_runs(&mut sixel, mask, cell_width);
+        }
+        sixel.push('-');
+    }
+    sixel.push_str("\x1b\\");
+    sixel
+}
+
+/// Writes one color's pixel columns, run-length encoding repeated columns.
+fn push_runs(sixel: &mut String, mask: &[u8], cell_width: usize) {
+    let mut cells = mask.iter().peekable();
+    while let Some(&bits) = cells.next() {
+        let mut count = cell_width;
+        while cells.next_if_eq(&&bits).is_some() {
+            count += cell_width;
+        }
+        let symbol = char::from(b'?' + bits);
+        if count > 3 {
+            write!(sixel, "!{count}{symbol}").unwrap();
+        } else {
+            sixel.extend(std::iter::repeat_n(symbol, count));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fs, io, path::Path};
+
+/// Reads how bright each column of a binary PPM (`P6`) or PGM (`P5`) image is
+/// on average, for `--source-image`. The brightest column comes out as 1 and
+/// the others in proportion, so a dim picture still lights the fire fully.
+pub fn column_brightness(path: &Path) -> io::Result<Vec<f32>> {
+    let data = fs::read(path)?;
+    let mut header = Header {
+        data: &data,
+        position: 0,
+    };
+    let channels = match header.token()? {
+        "P5" => 1,
+        "P6" => 3,
+        _ => return Err(invalid("expected a binary PPM or PGM image")),
+    };
+    let width = header.number()?;
+    let height = header.number()?;
+    let max_value = header.number()?;
+    if width == 0 || height == 0 || !(1..=65535).contains(&max_value) {
+        return Err(invalid(
+            "the image has no pixels or an invalid maximum value",
+        ));
+    }
+    // A single whitespace separates the header from the pixels
+    let pixels = data.get(header.position + 1..).unwrap_or_default();
+    let sample_bytes = if max_value > 255 { 2 } else { 1 };
+    let row_bytes = width.saturating_mul(channels * sample_bytes);
+    if pixels.len() < row_bytes.saturating_mul(height) {
+        return Err(invalid("the image is cut short"));
+    }
+
+    let mut columns = vec![0.0; width];
+    for row in pixels.chunks_exact(row_bytes).take(height) {
+        let samples = row.chunks_exact(sample_bytes).map(|sample| match *sample {
+            [high, low] => u16::from_be_bytes([high, low]) as f32,
+            [value] => value as f32,
+            _ => unreachable!(),
+        });
+        let samples: Vec<f32> = samples.collect();
+        for (column, pixel) in columns.iter_mut().zip(samples.chunks_exact(channels)) {
+            *column += pixel.iter().sum::<f32>() / channels as f32;
+        }
+    }
+    let brightest = columns.iter().copied().fold(0.0, f32::max);
+    if brightest == 0.0 {
+        return Err(invalid("the image is completely dark"));
+    }
+    Ok(columns.iter().map(|&column| column / brightest).collect())
+}
+
+/// The text header of a PNM image, read one whitespace-separated token at a
+/// time. Comments run from `#` to the end of the line.
+struct Header<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Header<'_> {
+    fn token(&mut self) -> io::Result<&str> {
+        loop {
+            match self.data.get(self.position) {
+                Some(byte) if byte.is_ascii_whitespace() => self.position += 1,
+                Some(b'#') => {
+                    while self
+                        .data
+                        .get(self.position)
+                        .is_some_and(|&byte| byte != b'\n')
+                    {
+                        self.position += 1;
+                    }
+                }
+                Some(_) => break,
+                None => return Err(invalid("the image header is cut short")),
+            }
+        }
+        let start = self.position;
+        while (self.data.get(self.position)).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.position])
+            .map_err(|_| invalid("the image header is malformed"))
+    }
+
+    fn number(&mut self) -> io::Result<usize> {
+        let token = self.token()?;
+        token.parse().map_err(|_| {
+            invalid(&format!(
+                "expected a number in the image header, found '{token}'"
+            ))
+        })
+    }
+}
+
+fn invalid(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
@@ -0,0 +1,97 @@
+use crate::pixels;
+use fire_in_the_term::App;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// A rendered frame on its way to disk.
+struct Shot {
+    path: PathBuf,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+/// Saves a numbered PPM image of the flame every `interval` for stitching
+/// into a time-lapse. The files are written on a background thread, so a slow
+/// disk doesn't make the animation stutter. Dropping it waits for the queued
+/// shots and prints the first error, if any, to stderr.
+pub struct Timelapse {
+    dir: PathBuf,
+    interval: Duration,
+    next_shot: Instant,
+    count: usize,
+    // Dropped to tell the writer no more shots are coming
+    shots: Option<Sender<Shot>>,
+    writer: Option<JoinHandle<io::Result<()>>>,
+}
+
+impl Timelapse {
+    /// Creates `dir` if needed and starts the writer. The first shot is taken
+    /// on the next frame.
+    pub fn new(dir: &Path, interval: Duration) -> io::Result<Timelapse> {
+        fs::create_dir_all(dir)?;
+        let (shots, queue) = mpsc::channel();
+        let writer = thread::spawn(move || queue.iter().try_for_each(|shot| write_ppm(&shot)));
+        Ok(Timelapse {
+            dir: dir.to_path_buf(),
+            interval,
+            next_shot: Instant::now(),
+            count: 0,
+            shots: Some(shots),
+            writer: Some(writer),
+        })
+    }
+
+    /// Saves the current frame of `app` if a shot is due.
+    pub fn capture(&mut self, app: &App) {
+        let now = Instant::now();
+        if now < self.next_shot {
+            return;
+        }
+        // Stay on the original schedule, unless a pause left it far behind
+        self.next_shot = (self.next_shot + self.interval).max(now);
+
+        let Some(shots) = &self.shots else {
+            return;
+        };
+        let (width, height) = pixels::image_size(app);
+        let shot = Shot {
+            path: self.dir.join(format!("{:05}.ppm", self.count)),
+            width,
+            height,
+            pixels: pixels::render_pixels(app, width, height),
+        };
+        self.count += 1;
+        // The writer only hangs up after a failed write, which is reported on
+        // drop, so stop sending
+        if shots.send(shot).is_err() {
+            self.shots = None;
+        }
+    }
+}
+
+impl Drop for Timelapse {
+    fn drop(&mut self) {
+        self.shots = None;
+        let result = self.writer.take().map(JoinHandle::join);
+        match result {
+            Some(Ok(Err(err))) => eprintln!("Failed to save the timelapse: {err}"),
+            Some(Err(_)) => eprintln!("Failed to save the timelapse"),
+            _ => {}
+        }
+    }
+}
+
+/// Writes a shot as a binary PPM, which nearly every image tool can read.
+fn write_ppm(shot: &Shot) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(&shot.path)?);
+    write!(file, "P6\n{} {}\n255\n", shot.width, shot.height)?;
+    file.write_all(&shot.pixels)?;
+    file.flush()
+}
@@ -0,0 +1,99 @@
+use crate::{App, palette};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::{Paragraph, StatefulWidget, Widget},
+};
+
+/// Draws an [`App`]'s fire into an area, resizing the simulation to fit it.
+///
+/// ```
+/// use fire_in_the_term::{App, FireWidget, TICK_RATE};
+/// use ratatui::{Terminal, backend::TestBackend, prelude::*};
+///
+/// let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+/// let mut app = App::new(80, 20);
+/// app.update_fire(TICK_RATE);
+///
+/// terminal
+///     .draw(|frame| {
+///         let [header, fire] =
+///             Layout::vertical([Constraint::Length(4), Constraint::Fill(1)]).areas(frame.area());
+///         frame.render_widget("Fire in the Term", header);
+///         frame.render_stateful_widget(FireWidget, fire, &mut app);
+///     })
+///     .unwrap();
+/// assert_eq!(app.display_size(), (80, 20));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FireWidget;
+
+impl StatefulWidget for FireWidget {
+    type State = App;
+
+    fn render(self, area: Rect, buf: &mut Buffer, app: &mut App) {
+        app.resize(area.width as usize, area.height as usize);
+        app.render(area, buf);
+    }
+}
+
+/// Draws the fire at its current size, without resizing the simulation.
+/// Below full [`App::opacity`] the fire's colors are blended with what is
+/// already in the buffer, and its blank cells leave it untouched.
+///
+/// ```
+/// use fire_in_the_term::{App, TICK_RATE};
+/// use ratatui::{buffer::Buffer, layout::Rect, style::{Color, Style}, widgets::Widget};
+///
+/// let area = Rect::new(0, 0, 40, 20);
+/// let mut app = App::new(40, 20).with_opacity(0.5);
+/// for _ in 0..50 {
+///     app.update_fire(TICK_RATE);
+/// }
+/// let mut buf = Buffer::empty(area);
+/// buf.set_style(area, Style::new().bg(Color::Blue));
+/// buf.set_string(0, 0, "Dashboard", Color::White);
+/// app.render(area, &mut buf);
+/// // The text above the flame shows through the fire's blank cells
+/// assert_eq!(buf[(0, 0)].symbol(), "D");
+/// // And the flame's colors are tinted with the blue under it
+/// let Color::Rgb(_, _, blue) = buf[(20, 19)].fg else { panic!() };
+/// assert!(blue > 100);
+/// ```
+impl Widget for &App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let fire = Paragraph::new(self.render_fire()).alignment(self.alignment());
+        let opacity = self.opacity();
+        if opacity >= 1.0 {
+            fire.render(area, buf);
+            return;
+        }
+        // Drawn on its own first, then laid over the buffer cell by cell
+        let mut layer = Buffer::empty(area);
+        fire.render(area, &mut layer);
+        for position in area.positions() {
+            let over = &layer[position];
+            if over.symbol() == " " && over.bg == Color::Reset {
+                continue;
+            }
+            let under = &mut buf[position];
+            let blend = |under: Color, over: Color| {
+                self.color_mode()
+                    .reduce(palette::blend(under, over, opacity))
+            };
+            // A faint fire leaves the text beneath it readable, only tinting it
+            if under.symbol() == " " || opacity >= 0.5 {
+                let fg = blend(under.bg, over.fg);
+                under.set_symbol(over.symbol()).set_fg(fg);
+            } else {
+                let fg = blend(under.fg, over.fg);
+                under.set_fg(fg);
+            }
+            if over.bg != Color::Reset {
+                let bg = blend(under.bg, over.bg);
+                under.set_bg(bg);
+            }
+        }
+    }
+}